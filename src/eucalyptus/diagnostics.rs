@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use super::*;
+
+const RED:   &str = "\x1b[1;31m";
+const BLUE:  &str = "\x1b[1;34m";
+const BOLD:  &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn label(&self) -> &'static str {
+        match *self {
+            Severity::Error   => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// a message about a place in the source, rendered with the offending line and a caret under it
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message:  String,
+    pub span:     Option<Span>,
+    pub notes:    Vec<String>,
+    pub help:     Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str, span: Option<Span>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.to_owned(),
+            span,
+            notes:   Vec::new(),
+            help:    None,
+        }
+    }
+
+    pub fn error(message: &str, span: Option<Span>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message, span)
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help = Some(help.to_owned());
+        self
+    }
+
+    // `name` is where the source came from, e.g. a file path or '<repl>'
+    pub fn render(&self, name: &str, source: &str, colour: bool) -> String {
+        let paint = |style: &str, text: &str| if colour {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_owned()
+        };
+
+        let style = match self.severity {
+            Severity::Error   => RED,
+            Severity::Warning => BOLD,
+        };
+
+        let mut out = format!("{}{}\n", paint(style, self.severity.label()), paint(BOLD, &format!(": {}", self.message)));
+
+        let span = match self.span {
+            Some(s) => s,
+            None    => {
+                writeln!(out, " {} {}", paint(BLUE, "-->"), name).unwrap();
+                self.render_footer(&mut out, 1, &paint);
+                return out
+            },
+        };
+
+        let number = span.start.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        writeln!(out, "{}{} {}:{}:{}", gutter, paint(BLUE, "-->"), name, span.start.line, span.start.col + 1).unwrap();
+
+        if let Some(line) = source.lines().nth(span.start.line - 1) {
+            // spans running past the first line are underlined to its end
+            let end = if span.end.line == span.start.line {
+                span.end.col
+            } else {
+                line.chars().count()
+            };
+
+            // keep tabs so the caret lines up with the source however it's displayed
+            let indent: String = line.chars().take(span.start.col).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+            let marker = "^".repeat(end.saturating_sub(span.start.col).max(1));
+
+            writeln!(out, "{} {}", gutter, paint(BLUE, "|")).unwrap();
+            writeln!(out, "{} {}", paint(BLUE, &format!("{} |", number)), line).unwrap();
+            writeln!(out, "{} {} {}{}", gutter, paint(BLUE, "|"), indent, paint(style, &marker)).unwrap();
+        }
+
+        self.render_footer(&mut out, gutter.len(), &paint);
+        out
+    }
+
+    fn render_footer(&self, out: &mut String, width: usize, paint: &dyn Fn(&str, &str) -> String) {
+        let gutter = " ".repeat(width);
+
+        for note in &self.notes {
+            writeln!(out, "{} {} {}", gutter, paint(BLUE, "="), paint(BOLD, &format!("note: {}", note))).unwrap();
+        }
+
+        if let Some(ref help) = self.help {
+            writeln!(out, "{} {} {}", gutter, paint(BLUE, "="), paint(BOLD, &format!("help: {}", help))).unwrap();
+        }
+    }
+}
+
+impl From<&LexerError> for Diagnostic {
+    fn from(e: &LexerError) -> Diagnostic {
+        Diagnostic::error(e.message(), Some(e.span))
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(e: &ParserError) -> Diagnostic {
+        Diagnostic::error(e.message(), e.span)
+    }
+}
+
+impl From<&RunError> for Diagnostic {
+    fn from(e: &RunError) -> Diagnostic {
+        Diagnostic {
+            notes: e.notes.clone(),
+            help:  e.help.as_ref().map(|h| h.to_string()),
+            ..Diagnostic::error(e.message(), e.span)
+        }
+    }
+}
+
+// every diagnostic rendered against `source`, separated by blank lines
+pub fn report(name: &str, source: &str, diagnostics: &[Diagnostic], colour: bool) -> String {
+    diagnostics.iter().map(|d| d.render(name, source, colour)).collect::<Vec<_>>().join("\n")
+}
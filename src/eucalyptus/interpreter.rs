@@ -0,0 +1,204 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::fs;
+use std::path::Path;
+
+use super::*;
+
+pub fn parse(source: &str) -> Result<Vec<Statement>, Vec<Diagnostic>> {
+    let tokens = lexer(&mut source.chars()).tokens().map_err(|errors| {
+        errors.iter().map(Diagnostic::from).collect::<Vec<_>>()
+    })?;
+
+    let traveler   = Traveler::new(tokens);
+    let mut parser = Parser::new(traveler);
+
+    parser.parse().map_err(|e| vec![Diagnostic::from(&e)])
+}
+
+// a global environment that scripts are run in, keeping their definitions between runs
+//
+// deep recursion needs a deep Rust stack, run it on a thread with room for the depth limit or lower the limit
+pub struct Interpreter {
+    symtab:  Rc<SymTab>,
+    typetab: Rc<TypeTab>,
+    valtab:  Rc<ValTab>,
+    output:  Output,
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        let interpreter = Interpreter {
+            symtab:  Rc::new(SymTab::new_global()),
+            typetab: Rc::new(TypeTab::new_global()),
+            valtab:  Rc::new(ValTab::new_global()),
+            output:  Rc::new(RefCell::new(Box::new(io::stdout()))),
+        };
+
+        define_builtins(&interpreter.symtab, &interpreter.typetab, &interpreter.valtab);
+        define_prelude(&interpreter.symtab, &interpreter.typetab, &interpreter.valtab, &interpreter.output);
+        interpreter
+    }
+
+    pub fn symtab(&self) -> &Rc<SymTab> {
+        &self.symtab
+    }
+
+    pub fn typetab(&self) -> &Rc<TypeTab> {
+        &self.typetab
+    }
+
+    pub fn valtab(&self) -> &Rc<ValTab> {
+        &self.valtab
+    }
+
+    pub fn set_strict(&self, strict: bool) {
+        self.valtab.set_strict(strict)
+    }
+
+    pub fn set_limits(&self, limits: Limits) {
+        self.valtab.context().set_limits(limits)
+    }
+
+    // sends what `print` and `println` write somewhere other than stdout
+    pub fn set_output<W: Write + 'static>(&self, out: W) {
+        *self.output.borrow_mut() = Box::new(out)
+    }
+
+    // runs `f`, putting the environment back as it was if it fails
+    //
+    // every `let` takes new slots, so dropping them undoes the definitions. assignments to
+    // existing ones stay done, like anything printed
+    fn atomically<T, F>(&self, f: F) -> Result<T, Vec<Diagnostic>>
+    where
+        F: FnOnce() -> Result<T, Vec<Diagnostic>>
+    {
+        self.undoing(f, |result| result.is_err())
+    }
+
+    // runs `f`, putting the environment back as it was when `undo` says so of what it gave
+    fn undoing<T, F>(&self, f: F, undo: fn(&Result<T, Vec<Diagnostic>>) -> bool) -> Result<T, Vec<Diagnostic>>
+    where
+        F: FnOnce() -> Result<T, Vec<Diagnostic>>
+    {
+        let symtab  = self.symtab.mark();
+        let typetab = self.typetab.mark();
+        let size    = self.valtab.size();
+
+        let result = f();
+
+        if undo(&result) {
+            self.symtab.rollback(&symtab);
+            self.typetab.rollback(&typetab);
+            self.valtab.truncate(size)
+        }
+
+        result
+    }
+
+    // checks and runs every statement, giving the value and type of the last. nothing is defined if any fails
+    pub fn run(&self, source: &str) -> Result<(Value, Type), Vec<Diagnostic>> {
+        self.atomically(|| self.run_statements(source))
+    }
+
+    fn run_statements(&self, source: &str) -> Result<(Value, Type), Vec<Diagnostic>> {
+        let stuff = parse(source)?;
+
+        self.valtab.context().reset();
+
+        let mut last = (Value::Nil, Type::Undefined);
+
+        for s in stuff.iter() {
+            if let Statement::Expression(ref e) = *s {
+                if **e == Expression::EOF {
+                    continue
+                }
+            }
+
+            let t = s.visit(&self.symtab, &self.typetab, &self.valtab)
+                .and_then(|_| s.get_type(&self.symtab, &self.typetab, &self.valtab))
+                .map_err(|e| vec![Diagnostic::from(&e)])?;
+
+            let t = self.typetab.resolve(&t);
+            let v = s.eval(&self.symtab, &self.valtab).map_err(|e| vec![Diagnostic::from(&e)])?;
+
+            last = (v, t)
+        }
+
+        Ok(last)
+    }
+
+    // checks without running, nothing it defines is kept
+    pub fn type_of(&self, source: &str) -> Result<Type, Vec<Diagnostic>> {
+        self.undoing(|| {
+            let mut last = Type::Undefined;
+
+            for s in parse(source)?.iter() {
+                s.visit(&self.symtab, &self.typetab, &self.valtab).map_err(|e| vec![Diagnostic::from(&e)])?;
+                last = s.get_type(&self.symtab, &self.typetab, &self.valtab).map_err(|e| vec![Diagnostic::from(&e)])?
+            }
+
+            Ok(self.typetab.resolve(&last))
+        }, |_| true)
+    }
+
+    pub fn eval_str(&self, source: &str) -> Result<Value, Vec<Diagnostic>> {
+        self.run(source).map(|(v, _)| v)
+    }
+
+    pub fn eval_file<P: AsRef<Path>>(&self, path: P) -> Result<Value, Vec<Diagnostic>> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(source) => self.eval_str(&source),
+            Err(e)     => Err(vec![Diagnostic::error(&format!("{}: {}", path.display(), e), None)]),
+        }
+    }
+
+    // binds `name` in the global scope, typed from the value
+    pub fn define_global(&self, name: &str, value: Value) {
+        let index = self.symtab.add_name(name);
+
+        while index >= self.typetab.size() {
+            self.typetab.grow()
+        }
+        while index >= self.valtab.size() {
+            self.valtab.grow()
+        }
+
+        self.typetab.set_type(index, 0, self.typetab.declare(&value.get_type())).unwrap();
+        self.valtab.set_value(index, 0, value).unwrap()
+    }
+
+    // binds a Rust function as `name`, `t` is checked against its uses when given
+    pub fn define_native<F>(&self, name: &str, arity: usize, t: Option<Type>, body: F)
+    where
+        F: Fn(&[Value]) -> RunResult<Value> + 'static
+    {
+        define_native(&self.symtab, &self.typetab, &self.valtab, Native::new(name, arity, t, body))
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        match self.symtab.get_bound(name) {
+            Some((index, 0)) => self.valtab.get_value(index, 0).ok(),
+            _                => None,
+        }
+    }
+
+    pub fn call_function(&self, name: &str, args: &[Value]) -> RunResult<Value> {
+        match self.get_global(name) {
+            Some(f) => {
+                self.valtab.context().reset();
+                apply(f, args.to_vec(), &self.valtab)
+            },
+            None    => Err(RunError::new(&format!("{}: undeclared function", name))),
+        }
+    }
+}
@@ -1,3 +1,7 @@
 pub mod syntax;
+pub mod diagnostics;
+pub mod interpreter;
 
 pub use self::syntax::*;
+pub use self::diagnostics::*;
+pub use self::interpreter::*;
@@ -0,0 +1,104 @@
+use std::rc::Rc;
+use std::fmt;
+
+use super::*;
+
+// the context is that of the running evaluation, for natives building values to charge them
+pub type NativeFn = dyn Fn(&[Value], &Context) -> RunResult<Value>;
+
+// a function implemented in Rust, called with arguments already evaluated
+#[derive(Clone)]
+pub struct Native {
+    pub name:  Rc<String>,
+    pub arity: usize,
+    // what the type checker sees, without one the arguments and result are unchecked
+    pub t:     Option<Type>,
+    pub body:  Rc<NativeFn>,
+}
+
+impl Native {
+    pub fn new<F>(name: &str, arity: usize, t: Option<Type>, body: F) -> Native
+    where
+        F: Fn(&[Value]) -> RunResult<Value> + 'static
+    {
+        Native::with_context(name, arity, t, move |args, _| body(args))
+    }
+
+    pub fn with_context<F>(name: &str, arity: usize, t: Option<Type>, body: F) -> Native
+    where
+        F: Fn(&[Value], &Context) -> RunResult<Value> + 'static
+    {
+        Native {
+            name: Rc::new(name.to_owned()),
+            arity,
+            t,
+            body: Rc::new(body),
+        }
+    }
+
+    pub fn get_type(&self) -> Type {
+        match self.t {
+            Some(ref t) => t.clone(),
+            None        => Type::Function(vec![Type::Any; self.arity], Box::new(Type::Any)),
+        }
+    }
+
+    pub fn call(&self, args: &[Value], context: &Context) -> RunResult<Value> {
+        if args.len() != self.arity {
+            return Err(RunError::new(&format!("{}: expected {} argument(s), found {}", self.name, self.arity, args.len())))
+        }
+
+        (self.body)(args, context)
+    }
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Native").field(&self.name).finish()
+    }
+}
+
+// the same function, not just one doing the same thing
+impl PartialEq for Native {
+    fn eq(&self, other: &Native) -> bool {
+        Rc::ptr_eq(&self.body, &other.body)
+    }
+}
+
+// functions provided by the interpreter, bound in the global scope by `define_builtins`
+fn builtins() -> Vec<Native> {
+    vec![
+        // truncates toward zero like `as`, but refuses values an i64 can't hold
+        Native::new("int", 1, Some(Type::Function(vec![Type::Float], Box::new(Type::Int))), |args| match args[0] {
+            Value::Float(n) if n.is_finite() && n >= i64::MIN as f64 && n < i64::MAX as f64 => Ok(Value::Int(n as i64)),
+            Value::Float(n) => Err(RunError::new(&format!("int: {} doesn't fit in an integer", n))),
+            ref v           => Err(RunError::new(&format!("int: invalid argument {:?}", v))),
+        }),
+
+        Native::new("float", 1, Some(Type::Function(vec![Type::Int], Box::new(Type::Float))), |args| match args[0] {
+            Value::Int(n) => Ok(Value::Float(n as f64)),
+            ref v         => Err(RunError::new(&format!("float: invalid argument {:?}", v))),
+        }),
+    ]
+}
+
+// binds `native` under its name in the global scope
+pub fn define_native(sym: &SymTab, env: &TypeTab, val: &ValTab, native: Native) {
+    let index = sym.add_name(&native.name);
+
+    while index >= env.size() {
+        env.grow()
+    }
+    while index >= val.size() {
+        val.grow()
+    }
+
+    env.set_type(index, 0, env.declare(&native.get_type())).unwrap();
+    val.set_value(index, 0, Value::Native(native)).unwrap()
+}
+
+pub fn define_builtins(sym: &SymTab, env: &TypeTab, val: &ValTab) {
+    for native in builtins() {
+        define_native(sym, env, val, native)
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::Cell;
+use std::mem;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use stacker;
+
+use super::*;
+
+// deep enough for ordinary recursion, calls past the thread's stack go on the heap, see `grow`
+pub const DEFAULT_DEPTH: usize = 5000;
+
+// a call made with less stack than this left continues on a new segment of `STACK_SEGMENT` bytes
+const STACK_RED_ZONE: usize = 1024 * 1024;
+const STACK_SEGMENT: usize  = 16 * 1024 * 1024;
+
+// what an array of `len` elements is charged against the memory budget
+pub fn array_size(len: usize) -> usize {
+    len.saturating_mul(mem::size_of::<Rc<Value>>() + mem::size_of::<Value>())
+}
+
+// runs `f` on more stack if the thread's is running out, whatever size the embedder's thread has
+pub fn grow<R, F: FnOnce() -> R>(f: F) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, f)
+}
+
+// bounds on one evaluation, `None` leaves that one unchecked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub steps:   Option<u64>,
+    pub depth:   Option<usize>,
+    pub timeout: Option<Duration>,
+    // bytes of arrays, records and strings built, an approximation of what a script allocates
+    pub memory:  Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            steps:   None,
+            depth:   Some(DEFAULT_DEPTH),
+            timeout: None,
+            memory:  None,
+        }
+    }
+}
+
+// what the running evaluation has used so far, shared by every scope like the strict flag
+#[derive(Debug)]
+pub struct Context {
+    limits:   Cell<Limits>,
+    steps:    Cell<u64>,
+    depth:    Cell<usize>,
+    memory:   Cell<usize>,
+    deadline: Cell<Option<Instant>>,
+}
+
+impl Context {
+    pub fn new(limits: Limits) -> Context {
+        Context {
+            limits:   Cell::new(limits),
+            steps:    Cell::new(0),
+            depth:    Cell::new(0),
+            memory:   Cell::new(0),
+            deadline: Cell::new(None),
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits.get()
+    }
+
+    pub fn set_limits(&self, limits: Limits) {
+        self.limits.set(limits)
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps.get()
+    }
+
+    pub fn memory(&self) -> usize {
+        self.memory.get()
+    }
+
+    // starts counting from nothing, before running a script or a repl input
+    pub fn reset(&self) {
+        self.steps.set(0);
+        self.depth.set(0);
+        self.memory.set(0);
+        self.deadline.set(self.limits().timeout.map(|t| Instant::now() + t))
+    }
+
+    pub fn step(&self) -> RunResult<()> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+
+        if let Some(max) = self.limits().steps {
+            if steps > max {
+                return Err(RunError::new(&format!("step limit of {} exceeded", max)))
+            }
+        }
+
+        // reading the clock costs more than a step, so it's only done now and then
+        if steps.is_multiple_of(1024) {
+            if let Some(deadline) = self.deadline.get() {
+                if Instant::now() >= deadline {
+                    return Err(RunError::new(&format!("timed out after {:?}", self.limits().timeout.unwrap())))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // a function call, which holds its depth until the returned frame is dropped
+    pub fn enter(&self) -> RunResult<Frame<'_>> {
+        let depth = self.depth.get() + 1;
+
+        if let Some(max) = self.limits().depth {
+            if depth > max {
+                return Err(RunError::new(&format!("call depth limit of {} exceeded", max)))
+            }
+        }
+
+        self.depth.set(depth);
+
+        Ok(
+            Frame {
+                context: self,
+            }
+        )
+    }
+
+    pub fn allocate(&self, bytes: usize) -> RunResult<()> {
+        let memory = self.memory.get().saturating_add(bytes);
+        self.memory.set(memory);
+
+        match self.limits().memory {
+            Some(max) if memory > max => Err(RunError::new(&format!("memory budget of {} bytes exceeded", max))),
+            _                         => Ok(()),
+        }
+    }
+}
+
+pub struct Frame<'a> {
+    context: &'a Context,
+}
+
+impl<'a> Drop for Frame<'a> {
+    fn drop(&mut self) {
+        self.context.depth.set(self.context.depth.get() - 1)
+    }
+}
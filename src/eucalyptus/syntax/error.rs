@@ -1,3 +1,5 @@
+use super::Span;
+
 use std::fmt;
 
 #[derive(Debug)]
@@ -7,13 +9,51 @@ pub enum RunErrorValue {
 
 #[derive(Debug)]
 pub struct RunError {
-    value: RunErrorValue,
+    value:     RunErrorValue,
+    pub span:  Option<Span>,
+    // more on what went wrong, and a suggestion, for the diagnostic
+    pub notes: Vec<String>,
+    pub help:  Option<Box<str>>,
 }
 
 impl RunError {
     pub fn new(value: &str) -> RunError {
         RunError {
-            value:    RunErrorValue::Constant(value.to_owned()),
+            value: RunErrorValue::Constant(value.to_owned()),
+            span:  None,
+            notes: Vec::new(),
+            help:  None,
+        }
+    }
+
+    pub fn new_pos(span: Span, value: &str) -> RunError {
+        RunError {
+            value: RunErrorValue::Constant(value.to_owned()),
+            span:  Some(span),
+            notes: Vec::new(),
+            help:  None,
+        }
+    }
+
+    // places an error raised without a position at `span`, keeping any more precise one
+    pub fn or_span(mut self, span: Span) -> RunError {
+        self.span.get_or_insert(span);
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> RunError {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> RunError {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        match self.value {
+            RunErrorValue::Constant(ref s) => s,
         }
     }
 }
@@ -21,7 +61,10 @@ impl RunError {
 impl fmt::Display for RunError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.value {
-            RunErrorValue::Constant(ref s) => write!(f, "{}", s),
+            RunErrorValue::Constant(ref s) => match self.span {
+                Some(span) => write!(f, "{}: {}", span, s),
+                None       => write!(f, "{}", s),
+            }
         }
     }
 }
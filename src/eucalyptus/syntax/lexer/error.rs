@@ -0,0 +1,37 @@
+use super::Span;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum LexerErrorValue {
+    Constant(String),
+}
+
+#[derive(Debug)]
+pub struct LexerError {
+    value:    LexerErrorValue,
+    pub span: Span,
+}
+
+impl LexerError {
+    pub fn new(span: Span, value: &str) -> LexerError {
+        LexerError {
+            value: LexerErrorValue::Constant(value.to_owned()),
+            span,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self.value {
+            LexerErrorValue::Constant(ref s) => s,
+        }
+    }
+}
+
+impl fmt::Display for LexerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            LexerErrorValue::Constant(ref s) => write!(f, "{}: {}", self.span, s),
+        }
+    }
+}
@@ -1,17 +1,19 @@
 use super::Tokenizer;
 use super::matcher::*;
-use super::token::{Token, TokenType};
+use super::token::{Span, Token, TokenType};
+use super::{LexerError, LexerResult};
 
 use std::str::Chars;
 use std::rc::Rc;
+use std::collections::VecDeque;
 
 pub fn lexer(data: &mut Chars) -> Lexer {
     let tokenizer = Tokenizer::new(data);
     let mut lexer = Lexer::new(tokenizer);
 
-    let eol = vec!["\n"].iter().map(|&x| x.to_string()).collect();
+    let eol = ["\n"].iter().map(|&x| x.to_string()).collect();
 
-    let symbols = vec![
+    let symbols = [
         "->",
         "(",
         ")",
@@ -26,9 +28,11 @@ pub fn lexer(data: &mut Chars) -> Lexer {
         "|",
         "=",
         ".",
+        "?",
+        ";",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let operators = vec![
+    let operators = [
         "+",
         "-",
         "*",
@@ -42,21 +46,30 @@ pub fn lexer(data: &mut Chars) -> Lexer {
         "<=",
         "==",
         "!=",
+        "|>",
+        "<|",
+        ">>",
+        "<<",
     ].iter().map(|&x| x.to_string()).collect();
     
-    let indent = vec![
+    // spelled like identifiers, so they're matched as whole words
+    let word_operators = [
+        "and", "or",
+    ].iter().map(|&x| x.to_string()).collect();
+
+    let indent = [
         "  ", "\t",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let types = vec![
+    let types = [
         "i32", "i64", "f32", "f64", "char", "string", "bool",
     ].iter().map(|&x| x.to_string()).collect();
     
-    let keywords = vec![
-        "let", "fun",
+    let keywords = [
+        "let", "rec", "fun", "if", "then", "elif", "else", "match", "with", "not", "type", "of",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let boolean = vec![
+    let boolean = [
         "true",
         "false",
     ].iter().map(|&x| x.to_string()).collect();
@@ -65,10 +78,13 @@ pub fn lexer(data: &mut Chars) -> Lexer {
     let matcher_indent         = ConstantMatcher::new(TokenType::Indent, indent);
     let matcher_symbol         = ConstantMatcher::new(TokenType::Symbol, symbols);
     let matcher_operator       = ConstantMatcher::new(TokenType::Operator, operators);
+    let matcher_symbolic       = LongestMatcher::new(vec![Rc::new(matcher_symbol), Rc::new(matcher_operator)]);
     let matcher_boolean        = KeyMatcher::new(TokenType::BoolLiteral, boolean);
     let matcher_types          = KeyMatcher::new(TokenType::Type, types);
     let matcher_keywords       = KeyMatcher::new(TokenType::Keyword, keywords);
+    let matcher_word_operators = KeyMatcher::new(TokenType::Operator, word_operators);
     let matcher_whitespace     = WhitespaceMatcher {};
+    let matcher_comment        = CommentMatcher {};
     let matcher_int_literal    = IntLiteralMatcher {};
     let matcher_float_literal  = FloatLiteralMatcher {};
     let matcher_identifier     = IdentifierMatcher {};
@@ -77,21 +93,24 @@ pub fn lexer(data: &mut Chars) -> Lexer {
     lexer.matchers_mut().push(Rc::new(matcher_eol));
     lexer.matchers_mut().push(Rc::new(matcher_indent));
     lexer.matchers_mut().push(Rc::new(matcher_whitespace));
-    lexer.matchers_mut().push(Rc::new(matcher_symbol));
-    lexer.matchers_mut().push(Rc::new(matcher_operator));
+    lexer.matchers_mut().push(Rc::new(matcher_comment));
+    lexer.matchers_mut().push(Rc::new(matcher_symbolic));
     lexer.matchers_mut().push(Rc::new(matcher_float_literal));
     lexer.matchers_mut().push(Rc::new(matcher_int_literal));
     lexer.matchers_mut().push(Rc::new(matcher_string_literal));
     lexer.matchers_mut().push(Rc::new(matcher_boolean));
     lexer.matchers_mut().push(Rc::new(matcher_types));
     lexer.matchers_mut().push(Rc::new(matcher_keywords));
+    lexer.matchers_mut().push(Rc::new(matcher_word_operators));
     lexer.matchers_mut().push(Rc::new(matcher_identifier));
     lexer
 }
 
 pub struct Lexer {
-    tokenizer: Tokenizer,
-    matchers: Vec<Rc<Matcher>>,
+    tokenizer:  Tokenizer,
+    matchers:   Vec<Rc<dyn Matcher>>,
+    queue:      VecDeque<Token>,
+    line_start: bool,
 }
 
 #[allow(dead_code)]
@@ -99,49 +118,93 @@ impl Lexer {
     pub fn new(tokenizer: Tokenizer) -> Lexer {
         Lexer {
             tokenizer,
-            matchers: Vec::new(),
+            matchers:   Vec::new(),
+            queue:      VecDeque::new(),
+            line_start: true,
         }
     }
 
-    pub fn match_token(&mut self) -> Option<Token> {
+    pub fn match_token(&mut self) -> LexerResult<Option<Token>> {
         for matcher in &mut self.matchers {
-            match self.tokenizer.try_match_token(matcher.as_ref()) {
-                Some(t) => return Some(t),
+            match self.tokenizer.try_match_token(matcher.as_ref())? {
+                Some(t) => return Ok(Some(t)),
                 None => continue,
             }
         }
-        None
+        Ok(None)
+    }
+
+    // every token, or every error found along the way
+    pub fn tokens(self) -> Result<Vec<Token>, Vec<LexerError>> {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+
+        for t in self {
+            match t {
+                Ok(t)  => tokens.push(t),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
     }
 
-    pub fn matchers(&self) -> &Vec<Rc<Matcher>> {
+    pub fn matchers(&self) -> &Vec<Rc<dyn Matcher>> {
         &self.matchers
     }
 
-    pub fn matchers_mut(&mut self) -> &mut Vec<Rc<Matcher>> {
+    pub fn matchers_mut(&mut self) -> &mut Vec<Rc<dyn Matcher>> {
         &mut self.matchers
     }
 }
 
+// comments are skipped like whitespace, and a line holding nothing but
+// comments is dropped along with its indentation so it can't end a block
 impl Iterator for Lexer {
-    type Item = Token;
-
-    fn next(&mut self) -> Option<Token> {
-        let token = match self.match_token() {
-            Some(n) => n,
-            None    => {
-                println!("invalid token");
-                return None
-            },
-        };
-        match token.token_type {
-            TokenType::EOF => None,
-            TokenType::Whitespace => {
-                match self.next() {
-                    Some(t) => Some(t),
-                    None => None,
-                }
+    type Item = LexerResult<Token>;
+
+    fn next(&mut self) -> Option<LexerResult<Token>> {
+        if let Some(t) = self.queue.pop_front() {
+            return Some(Ok(t))
+        }
+
+        let mut commented = false;
+
+        loop {
+            let token = match self.match_token() {
+                Ok(Some(n)) => n,
+                Ok(None)    => {
+                    let position = self.tokenizer.pos;
+                    let c        = self.tokenizer.next().unwrap();
+
+                    return Some(Err(LexerError::new(Span::new(position, self.tokenizer.pos), &format!("invalid token '{}'", c))))
+                },
+                Err(e) => return Some(Err(e)),
+            };
+
+            match token.token_type {
+                TokenType::EOF        => return None,
+                TokenType::Whitespace => (),
+                TokenType::Comment    => commented = true,
+
+                TokenType::Indent if self.line_start => self.queue.push_back(token),
+
+                TokenType::EOL if self.line_start && commented => {
+                    self.queue.clear();
+                    commented = false
+                },
+
+                _ => {
+                    self.line_start = token.token_type == TokenType::EOL;
+                    self.queue.push_back(token);
+
+                    return self.queue.pop_front().map(Ok)
+                },
             }
-            _ => Some(token),
         }
     }
 }
@@ -1,6 +1,9 @@
 
+use std::rc::Rc;
+
 use super::Tokenizer;
-use super::token::{Token, TokenType};
+use super::token::{Span, Token, TokenType};
+use super::{LexerError, LexerResult};
 
 macro_rules! token {
     ($tokenizer:expr, $token_type:ident, $accum:expr) => {{
@@ -9,18 +12,25 @@ macro_rules! token {
     ($tokenizer:expr, $token_type:expr, $accum:expr) => {{
         let tokenizer  = $tokenizer  as &$crate::eucalyptus::syntax::lexer::Tokenizer;
         let token_type = $token_type as $crate::eucalyptus::syntax::lexer::token::TokenType;
-        Some(Token::new(token_type, tokenizer.last_position(), $accum))
+        Ok(Some(Token::new(token_type, tokenizer.last_position(), tokenizer.pos, $accum)))
+    }};
+}
+
+macro_rules! lexer_error {
+    ($tokenizer:expr, $($arg:tt)*) => {{
+        let tokenizer = $tokenizer as &$crate::eucalyptus::syntax::lexer::Tokenizer;
+        Err(LexerError::new(Span::new(tokenizer.last_position(), tokenizer.pos), &format!($($arg)*)))
     }};
 }
 
 pub trait Matcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token>;
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>>;
 }
 
 pub struct WhitespaceMatcher;
 
 impl Matcher for WhitespaceMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
         let mut found = false;
         while !tokenizer.end() && tokenizer.peek().unwrap().is_whitespace() {
             found = true;
@@ -29,42 +39,93 @@ impl Matcher for WhitespaceMatcher {
         if found {
             token!(tokenizer, Whitespace, String::new())
         } else {
-            None
+            Ok(None)
         }
     }
 }
 
+// '--' line comments and nestable '(* ... *)' block comments
+pub struct CommentMatcher;
+
+impl Matcher for CommentMatcher {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
+        let mut comment = String::new();
+
+        match (tokenizer.peek(), tokenizer.peek_n(1)) {
+            (Some(&'-'), Some(&'-')) => {
+                tokenizer.advance(2);
+                while !tokenizer.end() && tokenizer.peek() != Some(&'\n') {
+                    comment.push(tokenizer.next().unwrap())
+                }
+            },
+
+            (Some(&'('), Some(&'*')) => {
+                tokenizer.advance(2);
+
+                let mut depth = 1;
+
+                loop {
+                    match (tokenizer.peek(), tokenizer.peek_n(1)) {
+                        (Some(&'('), Some(&'*')) => {
+                            depth += 1;
+                            comment.push_str("(*");
+                            tokenizer.advance(2)
+                        },
+
+                        (Some(&'*'), Some(&')')) => {
+                            depth -= 1;
+                            tokenizer.advance(2);
+
+                            if depth == 0 {
+                                break
+                            }
+                            comment.push_str("*)")
+                        },
+
+                        (Some(_), _) => comment.push(tokenizer.next().unwrap()),
+                        (None, _)    => return lexer_error!(tokenizer, "unterminated block comment"),
+                    }
+                }
+            },
+
+            _ => return Ok(None),
+        }
+
+        token!(tokenizer, Comment, comment)
+    }
+}
+
 pub struct IntLiteralMatcher {}
 
 impl Matcher for IntLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
         let mut accum = String::new();
         let prefix = match tokenizer.peek() {
             Some(&'-') => Some(false),
             Some(&'+') => Some(true),
             _          => None,
         };
-        if let Some(_) = prefix {
+        if prefix.is_some() {
             tokenizer.advance(1)
         };
-        while !tokenizer.end() && tokenizer.peek().unwrap().is_digit(10) {
+        while !tokenizer.end() && tokenizer.peek().unwrap().is_ascii_digit() {
             accum.push(tokenizer.next().unwrap());
         }
         if !accum.is_empty() {
             let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<i64>() {
                     Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse int: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse int: {}", error),
                 }
             } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<u64>() {
                     Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse int: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse int: {}", error),
                 }
             };
             token!(tokenizer, IntLiteral, literal)
         } else {
-            None
+            Ok(None)
         }
     }
 }
@@ -72,7 +133,7 @@ impl Matcher for IntLiteralMatcher {
 pub struct FloatLiteralMatcher;
 
 impl Matcher for FloatLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
         let mut accum = String::new();
 
         let prefix = match tokenizer.peek() {
@@ -80,23 +141,29 @@ impl Matcher for FloatLiteralMatcher {
             Some(&'+') => Some(true),
             _          => None,
         };
-        if let Some(_) = prefix {
+        if prefix.is_some() {
             tokenizer.advance(1)
         };
 
-        let curr = tokenizer.next().unwrap();
-        if curr.is_digit(10) {
+        let curr = match tokenizer.next() {
+            Some(c) => c,
+            None    => return Ok(None),
+        };
+        if curr.is_ascii_digit() {
             accum.push(curr)
         } else if curr == '.' {
             accum.push_str("0.")
         } else {
-            return None
+            return Ok(None)
         }
+
+        let mut illegal_point = false;
+
         while !tokenizer.end() {
             let current = *tokenizer.peek().unwrap();
-            if !current.is_whitespace() && current.is_digit(10) || current == '.' {
+            if !current.is_whitespace() && current.is_ascii_digit() || current == '.' {
                 if current == '.' && accum.contains('.') {
-                    panic!("illegal decimal point")
+                    illegal_point = true
                 }
                 accum.push(tokenizer.next().unwrap())
             } else {
@@ -104,33 +171,35 @@ impl Matcher for FloatLiteralMatcher {
             }
         }
 
-        if accum == "0.".to_owned() {
-            None
+        if illegal_point {
+            lexer_error!(tokenizer, "illegal decimal point in '{}'", accum)
+        } else if accum == "0." {
+            Ok(None)
         } else if accum.contains('.') {
 
             let literal: String = if Some(false) == prefix {
                 match accum.parse::<f64>() {
                     Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse float: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse float: {}", error),
                 }
             } else {
                 match accum.parse::<f64>() {
                     Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse float: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse float: {}", error),
                 }
             };
 
             token!(tokenizer, FloatLiteral, literal)
         } else {
             let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<i64>() {
                     Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse int: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse int: {}", error),
                 }
             } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<u64>() {
                     Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse int: {}", error)
+                    Err(error) => return lexer_error!(tokenizer, "unable to parse int: {}", error),
                 }
             };
 
@@ -142,25 +211,26 @@ impl Matcher for FloatLiteralMatcher {
 pub struct StringLiteralMatcher {}
 
 impl Matcher for StringLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
         let mut raw_marker = false;
-        let delimeter  = match tokenizer.peek().unwrap() {
-            &'"'  => Some('"'),
-            &'\'' => Some('\''),
-            &'r' if tokenizer.peek_n(1) == Some(&'"') => {
+        let delimeter  = match *tokenizer.peek().unwrap() {
+            '"'  => Some('"'),
+            '\'' => Some('\''),
+            'r' if tokenizer.peek_n(1) == Some(&'"') => {
                 raw_marker = true;
                 tokenizer.advance(1);
 
                 Some('"')
             },
-            _ => return None,
+            _ => return Ok(None),
         };
         tokenizer.advance(1);
         let mut string       = String::new();
         let mut found_escape = false;
+        let mut bad_escape   = None;
         loop {
             if tokenizer.end() {
-                break
+                return lexer_error!(tokenizer, "unterminated literal")
             }
             if raw_marker {
                 if tokenizer.peek().unwrap() == &'"' {
@@ -175,32 +245,40 @@ impl Matcher for StringLiteralMatcher {
                             'n' => '\n',
                             'r' => '\r',
                             't' => '\t',
-                            s => panic!("invalid character escape: {}", s),
+                            s => {
+                                bad_escape.get_or_insert(s);
+                                s
+                            },
                         }
                     );
                     found_escape = false
                 } else {
-                    match tokenizer.peek().unwrap() {
-                        &'\\' => {
+                    match *tokenizer.peek().unwrap() {
+                        '\\' => {
                             tokenizer.next();
                             found_escape = true
                         },
-                        &c if &c == &delimeter.unwrap() => break,
+                        c if c == delimeter.unwrap() => break,
                         _ => string.push(tokenizer.next().unwrap()),
                     }
                 }
             }
         }
         tokenizer.advance(1);
+
+        if let Some(s) = bad_escape {
+            return lexer_error!(tokenizer, "invalid character escape: \\{}", s)
+        }
+
         match delimeter.unwrap() {
             '"'  => {
                 token!(tokenizer, StringLiteral, string)
             },
             _ => {
-                if string.len() == 1 {
+                if string.chars().count() == 1 {
                     token!(tokenizer, CharLiteral, string)
                 } else {
-                    panic!("invalid char literal")
+                    lexer_error!(tokenizer, "invalid char literal: '{}'", string)
                 }
             },
         }
@@ -215,25 +293,31 @@ pub struct ConstantMatcher {
 impl ConstantMatcher {
     pub fn new(token_type: TokenType, constants: Vec<String>) -> Self {
         ConstantMatcher {
-            token_type: token_type,
-            constants: constants,
+            token_type,
+            constants,
         }
     }
 }
 
 impl Matcher for ConstantMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
-        for constant in self.constants.clone() {
-            let dat = tokenizer.clone().take(constant.len());
-            if dat.size_hint().1.unwrap() != constant.len() {
-                return None
-            }
-            if dat.collect::<String>() == constant {
-                tokenizer.advance(constant.len());
-                return token!(tokenizer, self.token_type.clone(), constant)
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
+        let mut longest: Option<&String> = None;
+
+        for constant in &self.constants {
+            let matches = constant.chars().enumerate().all(|(i, c)| tokenizer.peek_n(i) == Some(&c));
+
+            if matches && longest.is_none_or(|l| constant.len() > l.len()) {
+                longest = Some(constant)
             }
         }
-        None
+
+        match longest {
+            Some(constant) => {
+                tokenizer.advance(constant.chars().count());
+                token!(tokenizer, self.token_type.clone(), constant.clone())
+            },
+            None => Ok(None),
+        }
     }
 }
 
@@ -252,35 +336,70 @@ impl KeyMatcher {
 }
 
 impl Matcher for KeyMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
-        for constant in self.constants.clone() {
-            let dat = tokenizer.clone().take(constant.len());
-            if dat.size_hint().1.unwrap() != constant.len() {
-                return None
-            }
-            if dat.collect::<String>() == constant {
-                match tokenizer.peek_n(constant.len()) {
-                    Some(c) => {
-                        if "_@?'".contains(*c) || c.is_alphanumeric() {
-                            return None
-                        }
-                    },
-                    
-                    None => (),
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
+        for constant in &self.constants {
+            let len = constant.chars().count();
+
+            if constant.chars().enumerate().all(|(i, c)| tokenizer.peek_n(i) == Some(&c)) {
+                if let Some(c) = tokenizer.peek_n(len) {
+                    if "_@?'".contains(*c) || c.is_alphanumeric() {
+                        return Ok(None)
+                    }
                 }
 
-                tokenizer.advance(constant.len());
-                return token!(tokenizer, self.token_type.clone(), constant)
+                tokenizer.advance(len);
+                return token!(tokenizer, self.token_type.clone(), constant.clone())
             }
         }
-        None
+        Ok(None)
+    }
+}
+
+// tries every matcher and keeps the one consuming the most input, e.g. '==' over '='
+pub struct LongestMatcher {
+    matchers: Vec<Rc<dyn Matcher>>,
+}
+
+impl LongestMatcher {
+    pub fn new(matchers: Vec<Rc<dyn Matcher>>) -> Self {
+        LongestMatcher {
+            matchers,
+        }
+    }
+}
+
+impl Matcher for LongestMatcher {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
+        let start = *tokenizer.index();
+
+        let mut longest: Option<(Token, usize)> = None;
+
+        for matcher in &self.matchers {
+            tokenizer.take_snapshot();
+
+            let result = matcher.try_match(tokenizer);
+            let len    = *tokenizer.index() - start;
+
+            tokenizer.rollback_snapshot();
+
+            if let Some(t) = result? {
+                if longest.as_ref().is_none_or(|&(_, l)| len > l) {
+                    longest = Some((t, len))
+                }
+            }
+        }
+
+        Ok(longest.map(|(t, len)| {
+            tokenizer.advance(len);
+            t
+        }))
     }
 }
 
 pub struct IdentifierMatcher;
 
 impl Matcher for IdentifierMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexerResult<Option<Token>> {
         let mut identifier = String::new();
         while !tokenizer.end() {
             let current = *tokenizer.peek().unwrap();
@@ -293,7 +412,7 @@ impl Matcher for IdentifierMatcher {
         if !identifier.is_empty() {
             token!(tokenizer, Identifier, identifier)
         } else {
-            None
+            Ok(None)
         }
     }
 }
@@ -2,8 +2,12 @@ pub mod token;
 pub mod matcher;
 pub mod tokenizer;
 pub mod lexer;
+pub mod error;
 
 pub use self::token::*;
 pub use self::matcher::*;
 pub use self::tokenizer::*;
 pub use self::lexer::*;
+pub use self::error::*;
+
+pub type LexerResult<T> = Result<T, LexerError>;
@@ -13,23 +13,22 @@ pub enum TokenType {
     Type,
     Keyword,
     Whitespace,
+    Comment,
     Indent,
     EOL,
     EOF,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct TokenPosition {
-    pub line: usize,
-    pub col:  usize,
+    pub line:   usize,
+    pub col:    usize,
+    pub offset: usize,
 }
 
 impl Default for TokenPosition {
     fn default() -> Self {
-        TokenPosition {
-            line: 1,
-            col: 0,
-        }
+        TokenPosition::new(1, 0, 0)
     }
 }
 
@@ -40,9 +39,37 @@ impl fmt::Display for TokenPosition {
 }
 
 impl TokenPosition {
-    pub fn new(line: usize, col: usize) -> TokenPosition {
+    pub fn new(line: usize, col: usize, offset: usize) -> TokenPosition {
         TokenPosition {
-            line, col,
+            line, col, offset,
+        }
+    }
+}
+
+// source range from the start of the first token to the end of the last
+#[derive(Copy, Clone, PartialEq)]
+pub struct Span {
+    pub start: TokenPosition,
+    pub end:   TokenPosition,
+}
+
+// kept short, spans show up all over dumped syntax trees
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}..{}:{}", self.start.line, self.start.col, self.end.line, self.end.col)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
+impl Span {
+    pub fn new(start: TokenPosition, end: TokenPosition) -> Span {
+        Span {
+            start, end,
         }
     }
 }
@@ -51,30 +78,32 @@ impl TokenPosition {
 pub struct Token {
     pub token_type: TokenType,
     pub position:   TokenPosition,
+    pub end:        TokenPosition,
     content:        String,
 }
 
 #[allow(dead_code)]
 impl Token {
-    pub fn new(token_type: TokenType, position: TokenPosition, content: String) -> Token {
+    pub fn new(token_type: TokenType, position: TokenPosition, end: TokenPosition, content: String) -> Token {
         Token {
             token_type,
             position,
+            end,
             content,
         }
     }
 
+    pub fn span(&self) -> Span {
+        Span::new(self.position, self.end)
+    }
+
     pub fn content(&self) -> &String {
         &self.content
     }
 }
 
-impl<'a> PartialEq for Token {
+impl PartialEq for Token {
     fn eq(&self, other: &Token) -> bool {
         self.token_type == other.token_type
     }
-
-    fn ne(&self, other: &Token) -> bool {
-        self.token_type != other.token_type
-    }
 }
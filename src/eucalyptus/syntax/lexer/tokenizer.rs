@@ -1,5 +1,6 @@
 use super::Matcher;
 use super::{Token, TokenType, TokenPosition};
+use super::LexerResult;
 
 #[derive(Clone, Debug)]
 pub struct Snapshot {
@@ -34,7 +35,7 @@ impl Iterator for Tokenizer {
 
 #[allow(dead_code)]
 impl Tokenizer {
-    pub fn new(items: &mut Iterator<Item = char>) -> Tokenizer {
+    pub fn new(items: &mut dyn Iterator<Item = char>) -> Tokenizer {
         Tokenizer {
             index:     0,
             pos:       TokenPosition::default(),
@@ -80,6 +81,7 @@ impl Tokenizer {
                     }
                     _ => self.pos.col += 1
                 }
+                self.pos.offset += item.len_utf8()
             }
             self.index += a
         }
@@ -107,23 +109,27 @@ impl Tokenizer {
         self.peek_snapshot().unwrap().pos
     }
 
-    pub fn try_match_token(&mut self, matcher: &Matcher) -> Option<Token> {
+    pub fn try_match_token(&mut self, matcher: &dyn Matcher) -> LexerResult<Option<Token>> {
         if self.end() {
-            return Some(Token::new(TokenType::EOF,
-                                   TokenPosition::new(self.index, self.index),
-                                   String::new()));
+            return Ok(Some(Token::new(TokenType::EOF, self.pos, self.pos, String::new())));
         }
 
         self.take_snapshot();
         match matcher.try_match(self) {
-            Some(t) => {
+            Ok(Some(t)) => {
                 self.commit_snapshot();
-                Some(t)
+                Ok(Some(t))
             }
 
-            None => {
+            Ok(None) => {
                 self.rollback_snapshot();
-                None
+                Ok(None)
+            }
+
+            // the bad input stays consumed, so lexing picks up after it
+            Err(e) => {
+                self.commit_snapshot();
+                Err(e)
             }
         }
     }
@@ -4,6 +4,9 @@ pub mod symtab;
 pub mod typetab;
 pub mod valtab;
 pub mod error;
+pub mod builtin;
+pub mod context;
+pub mod prelude;
 
 pub use self::lexer::*;
 pub use self::parser::*;
@@ -11,5 +14,8 @@ pub use self::symtab::*;
 pub use self::typetab::*;
 pub use self::valtab::*;
 pub use self::error::*;
+pub use self::builtin::*;
+pub use self::context::*;
+pub use self::prelude::*;
 
 pub type RunResult<T> = Result<T, RunError>;
@@ -1,4 +1,6 @@
 use std::rc::Rc;
+use std::convert::TryFrom;
+use std::collections::BTreeMap;
 
 use super::*;
 
@@ -14,28 +16,148 @@ pub trait Typer {
     fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type>;
 }
 
+// the result of evaluating in tail position: a value, or a call left for the caller to make
+pub enum Tail {
+    Value(Value),
+    Call(Value, Vec<Value>, Span),
+}
+
+impl Tail {
+    // makes pending calls until one gives a value, so tail calls don't nest on the Rust stack
+    pub fn run(self, env: &ValTab) -> RunResult<Value> {
+        let (mut callee, mut args, mut span) = match self {
+            Tail::Value(v)            => return Ok(v),
+            Tail::Call(c, args, span) => (c, args, span),
+        };
+
+        // tail calls below reuse this frame
+        let _frame = env.context().enter().map_err(|e| e.or_span(span))?;
+
+        loop {
+            match grow(|| call(callee, args, env)).map_err(|e| e.or_span(span))? {
+                Tail::Value(v)      => return Ok(v),
+                Tail::Call(c, a, s) => {
+                    callee = c;
+                    args   = a;
+                    span   = s
+                },
+            }
+        }
+    }
+}
+
+// one call, leaving any call in tail position of the body unmade
+//
+// too few arguments give a function taking the rest, too many go to the function the call returns
+fn call(callee: Value, mut args: Vec<Value>, env: &ValTab) -> RunResult<Tail> {
+    match callee {
+        Value::Native(native) => if args.len() < native.arity {
+            let name  = native.name.clone();
+            let arity = native.arity - args.len();
+
+            Ok(Tail::Value(Value::Native(Native::with_context(&name, arity, None, move |rest, context| {
+                native.call(&[&args[..], rest].concat(), context)
+            }))))
+        } else if args.len() > native.arity {
+            let rest = args.split_off(native.arity);
+
+            call(native.call(&args, env.context())?, rest, env)
+        } else {
+            Ok(Tail::Value(native.call(&args, env.context())?))
+        },
+
+        Value::Function(params, body, captured_sym, captured_val) => {
+            if args.len() < params.len() {
+                // the given arguments are bound in a scope around the remaining parameters
+                let (bound, rest) = params.split_at(args.len());
+
+                let bound_sym = Rc::new(SymTab::new(captured_sym, bound));
+                let bound_env = Rc::new(ValTab::new(captured_val, &args));
+
+                return Ok(Tail::Value(Value::Function(rest.to_vec(), body, bound_sym, bound_env)))
+            }
+
+            if args.len() > params.len() {
+                let rest = args.split_off(params.len());
+                let f    = call(Value::Function(params, body, captured_sym, captured_val), args, env)?.run(env)?;
+
+                return call(f, rest, env)
+            }
+
+            let local_sym = Rc::new(SymTab::new(captured_sym, &params));
+            let local_env = Rc::new(ValTab::new(captured_val, &args));
+
+            block_tail(&body, &local_sym, &local_env)
+        },
+
+        c => env.or_nil(RunError::new(&format!("{}: can't call a non-function", c.kind()))).map(Tail::Value),
+    }
+}
+
+// calls a function value from outside of any script
+pub fn apply(callee: Value, args: Vec<Value>, env: &ValTab) -> RunResult<Value> {
+    let _frame = env.context().enter()?;
+
+    call(callee, args, env)?.run(env)
+}
+
+// calls a function value in the scope it was defined in, for Rust code without a scope of its own
+pub fn invoke(f: &Value, args: Vec<Value>, context: &Context) -> RunResult<Value> {
+    match *f {
+        Value::Function(_, _, _, ref env) => apply(f.clone(), args, env),
+        Value::Native(ref native)         => native.call(&args, context),
+        ref c                             => Err(RunError::new(&format!("{}: can't call a non-function", c.kind()))),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Block(Vec<Statement>),
-    Number(f64),
+    Int(i64),
+    Float(f64),
     Bool(bool),
     Str(Rc<String>),
     Char(char),
     Array(Vec<Rc<Expression>>),
-    Identifier(Rc<String>),
+    Identifier(Rc<String>, Span),
     Operation(Operation),
+    Unary(Unary),
     Lambda(Lambda),
     Call(Call),
     Index(Index),
+    Record(Record),
+    Field(Field),
+    If(If),
+    Match(Match),
     EOF,
 }
 
+impl Expression {
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            Expression::Identifier(_, span)      => Some(span),
+            Expression::Operation(ref operation) => Some(operation.span),
+            Expression::Unary(ref unary)         => Some(unary.span),
+            Expression::Lambda(ref lambda)       => Some(lambda.span),
+            Expression::Call(ref call)           => Some(call.span),
+            Expression::Index(ref index)         => Some(index.span),
+            Expression::Record(ref record)       => Some(record.span),
+            Expression::Field(ref field)         => Some(field.span),
+            Expression::If(ref if_)              => Some(if_.span),
+            Expression::Match(ref match_)        => Some(match_.span),
+            _                                    => None,
+        }
+    }
+}
+
 impl Visitor for Expression {
     fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
         match *self {
             Expression::Block(ref statements) => {
+                let (local_sym, local_env, local_val) = block_scope(sym, env, val);
+
                 for s in statements {
-                    s.visit(sym, env, val)?
+                    s.visit(&local_sym, &local_env, &local_val)?
                 }
                 Ok(())
             },
@@ -47,46 +169,46 @@ impl Visitor for Expression {
                 Ok(())
             }
 
-            Expression::Identifier(ref id) => match sym.get_name(&*id) {
+            Expression::Identifier(ref id, span) => match sym.get_name(id) {
                 Some(_) => Ok(()),
-                None    => Err(RunError::new(&format!("{}: undeclared use", id))),
+                None    => Err(undeclared(id, span)),
             },
 
             Expression::Operation(ref operation) => operation.visit(sym, env, val),
+            Expression::Unary(ref unary)         => unary.expr.visit(sym, env, val),
             Expression::Call(ref call)           => call.visit(sym, env, val),
             Expression::Index(ref index)         => index.visit(sym, env, val),
+            Expression::Record(ref record)       => record.visit(sym, env, val),
+            Expression::Field(ref field)         => field.record.visit(sym, env, val),
+            Expression::If(ref if_)              => if_.visit(sym, env, val),
+            Expression::Match(ref match_)        => match_.visit(sym, env, val),
 
             _ => Ok(()),
         }
     }
 }
 
+fn undeclared(name: &str, span: Span) -> RunError {
+    RunError::new_pos(span, &format!("{}: undeclared use", name))
+        .with_help("a name is seen after its `let`, and only inside the block defining it")
+}
+
 impl Evaluator for Expression {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        env.context().step()?;
+
         match *self {
-            Expression::Number(n)  => Ok(Value::Number(n)),
+            Expression::Int(n)     => Ok(Value::Int(n)),
+            Expression::Float(n)   => Ok(Value::Float(n)),
             Expression::Bool(n)    => Ok(Value::Bool(n)),
             Expression::Str(ref n) => Ok(Value::Str(n.clone())),
             Expression::Char(n)    => Ok(Value::Char(n)),
             
             Expression::Block(ref statements) => {
-                let mut statements_trim = Vec::new();
-                for s in statements {
-                    s.eval(sym, env)?;
-                    match *s {
-                        Statement::Expression(ref e) => match **e {
-                            Expression::EOF => (),
-                            ref c => statements_trim.push(Statement::Expression(Rc::new(c.clone()))),
-                        },
-
-                        ref c => statements_trim.push(c.clone()),
-                    }
-                }
+                let local_sym = Rc::new(SymTab::new(sym.clone(), &[]));
+                let local_env = Rc::new(ValTab::new(env.clone(), &[]));
 
-                match statements_trim.last() {
-                    Some(s) => Ok(s.eval(sym, env)?),
-                    None    => Err(RunError::new(&format!("found empty block"))),
-                }
+                block_tail(statements, &local_sym, &local_env)?.run(env)
             },
 
             Expression::Array(ref content) => {
@@ -96,29 +218,83 @@ impl Evaluator for Expression {
                     stack.push(Rc::new(c.eval(sym, env)?))
                 }
 
+                env.context().allocate(array_size(stack.len()))?;
+
                 Ok(Value::Array(stack))
             },
             
-            Expression::Index(ref index) => index.eval(sym, env),
+            Expression::Index(ref index)   => index.eval(sym, env),
+            Expression::Record(ref record) => record.eval(sym, env),
+            Expression::Field(ref field)   => field.eval(sym, env),
 
-            Expression::Identifier(ref id) => match sym.get_name(&*id) {
-                Some((a, b)) => Ok(env.get_value(a, b)?),
-                None         => Err(RunError::new(&format!("{}: undeclared use", id))),
+            Expression::Identifier(ref id, span) => match sym.get_bound(id) {
+                Some((a, b)) => env.get_value(a, b).map_err(|e| e.or_span(span)),
+                None         => Err(undeclared(id, span)),
             },
 
             Expression::Operation(ref operation) => operation.eval(sym, env),
+            Expression::Unary(ref unary)         => unary.eval(sym, env),
             Expression::Lambda(ref lambda)       => lambda.eval(sym, env),
             Expression::Call(ref call)           => call.eval(sym, env),
+            Expression::If(ref if_)              => if_.eval(sym, env),
+            Expression::Match(ref match_)        => match_.eval(sym, env),
 
             _ => Ok(Value::Nil),
         }
     }
 }
 
+impl Expression {
+    // evaluates up to a call in tail position, which is left to the caller
+    pub fn eval_tail(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Tail> {
+        match *self {
+            Expression::Block(_) | Expression::Call(_) | Expression::If(_) | Expression::Match(_) => env.context().step()?,
+            _                                                                                      => (),
+        }
+
+        match *self {
+            Expression::Block(ref statements) => {
+                let local_sym = Rc::new(SymTab::new(sym.clone(), &[]));
+                let local_env = Rc::new(ValTab::new(env.clone(), &[]));
+
+                block_tail(statements, &local_sym, &local_env)
+            },
+            Expression::Call(ref call)        => call.eval_tail(sym, env),
+            Expression::If(ref if_)           => if_.eval_tail(sym, env),
+            Expression::Match(ref match_)     => match_.eval_tail(sym, env),
+
+            ref e => Ok(Tail::Value(e.eval(sym, env)?)),
+        }
+    }
+}
+
+// runs every statement but the last, which is in tail position
+fn block_tail(statements: &[Statement], sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Tail> {
+    let statements = statements.iter().filter(|s| match **s {
+        Statement::Expression(ref e) => **e != Expression::EOF,
+        _                            => true,
+    }).collect::<Vec<_>>();
+
+    let (last, rest) = match statements.split_last() {
+        Some(s) => s,
+        None    => return Err(RunError::new("found empty block")),
+    };
+
+    for s in rest {
+        s.eval(sym, env)?;
+    }
+
+    match **last {
+        Statement::Expression(ref e) => e.eval_tail(sym, env),
+        ref s                        => Ok(Tail::Value(s.eval(sym, env)?)),
+    }
+}
+
 impl Typer for Expression {
     fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
         match *self {
-            Expression::Number(_)          => Ok(Type::Number),
+            Expression::Int(_)             => Ok(Type::Int),
+            Expression::Float(_)           => Ok(Type::Float),
             Expression::Str(_)             => Ok(Type::Str),
             Expression::Char(_)            => Ok(Type::Char),
             Expression::Bool(_)            => Ok(Type::Bool),
@@ -131,24 +307,64 @@ impl Typer for Expression {
 
                 Ok(Type::Array(types))
             }
-            Expression::Identifier(ref n) => match sym.get_name(&*n) {
+            Expression::Identifier(ref n, span) => match sym.get_name(n) {
                 Some((i, env_index)) => {
-                    Ok(env.get_type(i, env_index).unwrap())
+                    Ok(env.instantiate(&env.get_type(i, env_index).map_err(|e| e.or_span(span))?))
                 },
-                None => Err(RunError::new(&format!("{}: can't get type of undeclared", n))),
+                None => Err(RunError::new_pos(span, &format!("{}: can't get type of undeclared", n))),
+            },
+            Expression::Block(ref statements)    => {
+                let (local_sym, local_env, local_val) = block_scope(sym, env, val);
+
+                block_type(statements, &local_sym, &local_env, &local_val)
             },
             Expression::Operation(ref operation) => operation.get_type(sym, env, val),
+            Expression::Unary(ref unary)         => unary.get_type(sym, env, val),
+            Expression::Lambda(ref lambda)       => lambda.get_type(sym, env, val),
+            Expression::Call(ref call)           => call.get_type(sym, env, val),
             Expression::Index(ref index)         => index.get_type(sym, env, val),
+            Expression::Record(ref record)       => record.get_type(sym, env, val),
+            Expression::Field(ref field)         => field.get_type(sym, env, val),
+            Expression::If(ref if_)              => if_.get_type(sym, env, val),
+            Expression::Match(ref match_)        => match_.get_type(sym, env, val),
             _ => Ok(Type::Undefined),
         }
     }
 }
 
+// a block binds in a scope of its own, so nothing it defines is seen after it
+fn block_scope(sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> (Rc<SymTab>, Rc<TypeTab>, Rc<ValTab>) {
+    (
+        Rc::new(SymTab::new(sym.clone(), &[])),
+        Rc::new(TypeTab::new(env.clone(), &[])),
+        Rc::new(ValTab::new(val.clone(), &[])),
+    )
+}
+
+// types the statements in order so each sees the bindings before it, the block has the type of the last
+fn block_type(statements: &[Statement], sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+    let mut last = Type::Undefined;
+
+    for s in statements {
+        if let Statement::Expression(ref e) = *s {
+            if **e == Expression::EOF {
+                continue
+            }
+        }
+
+        s.visit(sym, env, val)?;
+        last = s.get_type(sym, env, val)?
+    }
+
+    Ok(last)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Operation {
     pub left:  Rc<Expression>,
     pub op:    Operand,
     pub right: Rc<Expression>,
+    pub span:  Span,
 }
 
 impl Visitor for Operation {
@@ -158,182 +374,702 @@ impl Visitor for Operation {
     }
 }
 
+impl Operation {
+    // integer arithmetic, reporting overflow and division by zero instead of wrapping or panicking
+    fn int(&self, a: i64, b: i64) -> RunResult<Value> {
+        let result = match self.op {
+            Operand::Add => a.checked_add(b),
+            Operand::Sub => a.checked_sub(b),
+            Operand::Mul => a.checked_mul(b),
+
+            Operand::Div | Operand::Mod if b == 0 => {
+                return Err(RunError::new_pos(self.span, &format!("({}{}{}): division by zero", a, self.op.symbol(), b)))
+            },
+
+            // truncating, so -7 / 2 is -3 and -7 % 2 is -1
+            Operand::Div => a.checked_div(b),
+            Operand::Mod => a.checked_rem(b),
+
+            Operand::Pow => match u32::try_from(b) {
+                Ok(b)  => a.checked_pow(b),
+                Err(_) => return Err(RunError::new_pos(self.span, &format!("({}{}{}): negative exponent", a, self.op.symbol(), b))),
+            },
+
+            _ => unreachable!(),
+        };
+
+        match result {
+            Some(n) => Ok(Value::Int(n)),
+            None    => Err(RunError::new_pos(self.span, &format!("({}{}{}): integer overflow", a, self.op.symbol(), b))),
+        }
+    }
+
+    fn mismatch(&self, env: &ValTab, a: &Value, b: &Value) -> RunResult<Value> {
+        env.or_nil(RunError::new_pos(self.span, &format!("({}{}{}): failed to operate", a.kind(), self.op.symbol(), b.kind())))
+    }
+
+    // `and` and `or`, the right side is only evaluated when the left doesn't decide the result
+    fn logic(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        let left = match self.left.eval(sym, env)? {
+            Value::Bool(b) => b,
+            c              => return env.or_nil(RunError::new_pos(self.span, &format!("{:?}: non-bool operand", c))),
+        };
+
+        match (&self.op, left) {
+            (Operand::And, false) => return Ok(Value::Bool(false)),
+            (Operand::Or, true)   => return Ok(Value::Bool(true)),
+            _                     => (),
+        }
+
+        match self.right.eval(sym, env)? {
+            Value::Bool(b) => Ok(Value::Bool(b)),
+            c              => env.or_nil(RunError::new_pos(self.span, &format!("{:?}: non-bool operand", c))),
+        }
+    }
+
+    // `f >> g` and `g << f`, a function giving what `f` returns to `g`
+    fn compose(&self, env: &ValTab, left: Value, right: Value) -> RunResult<Value> {
+        let (first, second) = match self.op {
+            Operand::Compose => (&left, &right),
+            _                => (&right, &left),
+        };
+
+        let arity = match (first, second) {
+            (&Value::Function(ref params, ..), &Value::Function(..)) |
+            (&Value::Function(ref params, ..), &Value::Native(_))    => params.len(),
+            (&Value::Native(ref native), &Value::Function(..))       |
+            (&Value::Native(ref native), &Value::Native(_))          => native.arity,
+            _                                                        => return self.mismatch(env, &left, &right),
+        };
+
+        let first  = first.clone();
+        let second = second.clone();
+
+        Ok(Value::Native(Native::with_context("composition", arity, None, move |args, context| {
+            invoke(&second, vec![invoke(&first, args.to_vec(), context)?], context)
+        })))
+    }
+}
+
 impl Evaluator for Operation {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        if let Operand::And | Operand::Or = self.op {
+            return self.logic(sym, env)
+        }
+
+        let left  = self.left.eval(sym, env)?;
+        let right = self.right.eval(sym, env)?;
+
         match self.op {
-            Operand::Pow => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a.powf(b))),
-                _ => Ok(Value::Nil),
+            Operand::Pow => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a.powf(b))),
+                (a, b) => self.mismatch(env, &a, &b),
             },
 
-            Operand::Mul => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a * b)),
-                _ => Ok(Value::Nil),
+            Operand::Mul => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a * b)),
+                (a, b) => self.mismatch(env, &a, &b),
             },
 
-            Operand::Div => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a / b)),
-                _ => Ok(Value::Nil),
+            Operand::Div => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a / b)),
+                (a, b) => self.mismatch(env, &a, &b),
             },
 
-            Operand::Mod => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a % b)),
-                _ => Ok(Value::Nil),
+            Operand::Mod => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a % b)),
+                (a, b) => self.mismatch(env, &a, &b),
             },
             
-            Operand::Add => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
+            Operand::Add => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a + b)),
                 (Value::Array(a), b)                 => {
+                    env.context().allocate(array_size(a.len() + 1))?;
+
                     let mut c = a.clone();
                     c.push(Rc::new(b));
                     Ok(Value::Array(c))
                 },
-                _ => Ok(Value::Nil),
+                (a, b) => self.mismatch(env, &a, &b),
             },
             
-            Operand::Sub => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a - b)),
-                _ => Ok(Value::Nil),
+            Operand::Sub => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => self.int(a, b),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Float(a - b)),
+                (a, b) => self.mismatch(env, &a, &b),
             },
             
-            Operand::Equal => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (a, b) => Ok(Value::Bool(a == b)),
-            },
+            Operand::Equal  => Ok(Value::Bool(left == right)),
 
-            Operand::NEqual => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (a, b) => Ok(Value::Bool(a != b)),
-            },
+            Operand::NEqual => Ok(Value::Bool(left != right)),
             
-            Operand::Lt => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(a < b)),
+            Operand::Lt => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => Ok(Value::Bool(a < b)),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Bool(a < b)),
                 (Value::Str(a), Value::Str(b))       => Ok(Value::Bool(a < b)),
                 (Value::Char(a), Value::Char(b))     => Ok(Value::Bool(a < b)),
                 (Value::Array(a), Value::Array(b))   => Ok(Value::Bool(a.len() < b.len())),
-                _ => Ok(Value::Nil),
+                (a, b) => self.mismatch(env, &a, &b),
             },
             
-            Operand::Gt => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(a > b)),
+            Operand::Gt => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => Ok(Value::Bool(a > b)),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Bool(a > b)),
                 (Value::Str(a), Value::Str(b))       => Ok(Value::Bool(a > b)),
                 (Value::Char(a), Value::Char(b))     => Ok(Value::Bool(a > b)),
                 (Value::Array(a), Value::Array(b))   => Ok(Value::Bool(a.len() > b.len())),
-                _ => Ok(Value::Nil),
+                (a, b) => self.mismatch(env, &a, &b),
             },
 
-            Operand::LtEqual => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(a <= b)),
+            Operand::LtEqual => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => Ok(Value::Bool(a <= b)),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Bool(a <= b)),
                 (Value::Str(a), Value::Str(b))       => Ok(Value::Bool(a <= b)),
                 (Value::Char(a), Value::Char(b))     => Ok(Value::Bool(a <= b)),
                 (Value::Array(a), Value::Array(b))   => Ok(Value::Bool(a.len() <= b.len())),
-                _ => Ok(Value::Nil),
+                (a, b) => self.mismatch(env, &a, &b),
             },
 
-            Operand::GtEqual => match (self.left.eval(sym, env)?, self.right.eval(sym, env)?) {
-                (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(a >= b)),
+            Operand::GtEqual => match (left, right) {
+                (Value::Int(a), Value::Int(b))       => Ok(Value::Bool(a >= b)),
+                (Value::Float(a), Value::Float(b))   => Ok(Value::Bool(a >= b)),
                 (Value::Str(a), Value::Str(b))       => Ok(Value::Bool(a >= b)),
+                (Value::Char(a), Value::Char(b))     => Ok(Value::Bool(a >= b)),
                 (Value::Array(a), Value::Array(b))   => Ok(Value::Bool(a.len() >= b.len())),
-                _ => Ok(Value::Nil),
+                (a, b) => self.mismatch(env, &a, &b),
             },
+
+            Operand::Compose | Operand::ComposeBack => self.compose(env, left, right),
+
+            // the parser turns pipes into calls
+            Operand::And | Operand::Or | Operand::Pipe | Operand::PipeBack => unreachable!(),
         }
     }
 }
 
 impl Typer for Operation {
     fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let left  = self.left.get_type(sym, env, val)?;
+        let right = self.right.get_type(sym, env, val)?;
+
+        self.operand_type(left, right, env).or_else(|e| val.or_any(e))
+    }
+}
+
+impl Operation {
+    fn operand_type(&self, left: Type, right: Type, env: &TypeTab) -> RunResult<Type> {
+        let failed = |what: &str| RunError::new_pos(
+            self.span, &format!("({}{}{}): failed to {}", env.resolve(&left), self.op.symbol(), env.resolve(&right), what)
+        );
+
+        // both sides share one type, an `Any` side takes the other's
+        let operands = |what: &str| -> RunResult<Type> {
+            env.unify(&left, &right).map_err(|_| failed(what))?;
+
+            match env.resolve(&left) {
+                Type::Any | Type::Undefined => Ok(env.resolve(&right)),
+                t                           => Ok(t),
+            }
+        };
+
+        // arithmetic on a type not known yet leaves it to be any number, so the function stays generic
+        let numbers = || match operands("operate")? {
+            t @ Type::Int | t @ Type::Float              => Ok(t),
+            t @ Type::Constrained(_, Constraint::Number) => Ok(t),
+            Type::Any | Type::Undefined                  => Ok(Type::Any),
+            t @ Type::Var(_) | t @ Type::Constrained(..) => {
+                env.unify(&env.fresh_constrained(Constraint::Number), &t).map_err(|_| failed("operate"))?;
+                Ok(env.resolve(&t))
+            },
+            _ => Err(failed("operate")),
+        };
+
         match self.op {
-            Operand::Pow => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}^{:?}): failed to operate", a, b)))
+            Operand::Pow | Operand::Mul | Operand::Div | Operand::Mod | Operand::Sub => numbers(),
+
+            Operand::Add => match env.resolve(&left) {
+                Type::Array(mut content) => {
+                    content.push(Rc::new(right.clone()));
+                    Ok(Type::Array(content))
+                },
+                Type::List(t) => {
+                    env.unify(&t, &right).map_err(|_| failed("operate"))?;
+                    Ok(Type::List(t))
+                },
+                _ => numbers(),
             },
-            
-            Operand::Mul => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}*{:?}): failed to operate", a, b)))
+
+            Operand::Equal | Operand::NEqual => {
+                env.unify(&left, &right).map_err(|_| failed("compare"))?;
+                Ok(Type::Bool)
             },
-            
-            Operand::Div => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}/{:?}): failed to operate", a, b)))
+
+            Operand::Lt | Operand::Gt | Operand::LtEqual | Operand::GtEqual => match operands("compare")? {
+                Type::Int | Type::Float | Type::Str | Type::Char | Type::Array(_) | Type::List(_) | Type::Any | Type::Undefined => Ok(Type::Bool),
+
+                Type::Constrained(..) => Ok(Type::Bool),
+
+                t @ Type::Var(_) => {
+                    env.unify(&env.fresh_constrained(Constraint::Comparable), &t).map_err(|_| failed("compare"))?;
+                    Ok(Type::Bool)
+                },
+
+                _ => Err(failed("compare")),
             },
-            
-            Operand::Mod => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}%{:?}): failed to operate", a, b)))
+
+            Operand::And | Operand::Or => {
+                env.unify(&Type::Bool, &left).map_err(|_| failed("operate"))?;
+                env.unify(&Type::Bool, &right).map_err(|_| failed("operate"))?;
+                Ok(Type::Bool)
             },
-            
-            Operand::Add => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}+{:?}): failed to operate", a, b)))
+
+            // the result of the first is the only argument of the second
+            Operand::Compose | Operand::ComposeBack => {
+                let (first, second) = match self.op {
+                    Operand::Compose => (&left, &right),
+                    _                => (&right, &left),
+                };
+
+                let ret = env.fresh();
+
+                match env.resolve(first) {
+                    Type::Function(params, mid) => {
+                        env.unify(second, &Type::Function(vec![*mid], Box::new(ret.clone()))).map_err(|_| failed("compose"))?;
+                        Ok(Type::Function(params, Box::new(ret)))
+                    },
+
+                    Type::Any | Type::Undefined => Ok(Type::Any),
+
+                    _ => {
+                        let param = env.fresh();
+                        let mid   = env.fresh();
+
+                        env.unify(first, &Type::Function(vec![param.clone()], Box::new(mid.clone()))).map_err(|_| failed("compose"))?;
+                        env.unify(second, &Type::Function(vec![mid], Box::new(ret.clone()))).map_err(|_| failed("compose"))?;
+                        Ok(Type::Function(vec![param], Box::new(ret)))
+                    },
+                }
             },
-            
-            Operand::Sub => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Number),
-                (Type::Any, Type::Number)    => Ok(Type::Any),
-                (Type::Number, Type::Any)    => Ok(Type::Any),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}-{:?}): failed to operate", a, b)))
+
+            Operand::Pipe | Operand::PipeBack => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unary {
+    pub op:   UnaryOperand,
+    pub expr: Rc<Expression>,
+    pub span: Span,
+}
+
+impl Evaluator for Unary {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        match (&self.op, self.expr.eval(sym, env)?) {
+            (UnaryOperand::Neg, Value::Int(n))   => match n.checked_neg() {
+                Some(n) => Ok(Value::Int(n)),
+                None    => Err(RunError::new_pos(self.span, &format!("(-{}): integer overflow", n))),
             },
-            
-            Operand::Lt => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Bool),
-                (Type::Str, Type::Str)       => Ok(Type::Bool),
-                (Type::Char, Type::Char)     => Ok(Type::Bool),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}<{:?}): failed to compare", a, b)))
+            (UnaryOperand::Neg, Value::Float(n)) => Ok(Value::Float(-n)),
+            (UnaryOperand::Not, Value::Bool(b))  => Ok(Value::Bool(!b)),
+
+            (op, c) => env.or_nil(RunError::new_pos(self.span, &format!("({}{}): failed to operate", op.symbol(), c.kind()))),
+        }
+    }
+}
+
+impl Typer for Unary {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let t = self.expr.get_type(sym, env, val)?;
+
+        let failed = || RunError::new_pos(self.span, &format!("({}{}): failed to operate", self.op.symbol(), env.resolve(&t)));
+
+        let typed = match self.op {
+            // any number, like the arithmetic operators
+            UnaryOperand::Neg => match env.resolve(&t) {
+                t @ Type::Int | t @ Type::Float              => Ok(t),
+                t @ Type::Constrained(_, Constraint::Number) => Ok(t),
+                Type::Any | Type::Undefined                  => Ok(Type::Any),
+                t @ Type::Var(_) | t @ Type::Constrained(..) => {
+                    env.unify(&env.fresh_constrained(Constraint::Number), &t).map_err(|_| failed())?;
+                    Ok(env.resolve(&t))
+                },
+                _ => Err(failed()),
             },
-            
-            Operand::Gt => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Bool),
-                (Type::Str, Type::Str)       => Ok(Type::Bool),
-                (Type::Char, Type::Char)     => Ok(Type::Bool),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}>{:?}): failed to compare", a, b)))
+
+            UnaryOperand::Not => env.unify(&Type::Bool, &t).map(|_| Type::Bool).map_err(|_| failed()),
+        };
+
+        typed.or_else(|e| val.or_any(e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct If {
+    pub cond:      Rc<Expression>,
+    pub body:      Rc<Expression>,
+    pub else_body: Rc<Expression>,
+    pub span:      Span,
+}
+
+impl Visitor for If {
+    fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
+        self.cond.visit(sym, env, val)?;
+        self.body.visit(sym, env, val)?;
+        self.else_body.visit(sym, env, val)
+    }
+}
+
+impl Evaluator for If {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        self.eval_tail(sym, env)?.run(env)
+    }
+}
+
+impl If {
+    // both branches are in tail position
+    fn eval_tail(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Tail> {
+        match self.cond.eval(sym, env)? {
+            Value::Bool(true)  => self.body.eval_tail(sym, env),
+            Value::Bool(false) => self.else_body.eval_tail(sym, env),
+            c => Err(RunError::new_pos(self.cond.span().unwrap_or(self.span), &format!("{:?}: non-bool condition", c))),
+        }
+    }
+}
+
+impl Typer for If {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let cond = self.cond.get_type(sym, env, val)?;
+
+        if env.unify(&Type::Bool, &cond).is_err() {
+            return Err(RunError::new_pos(self.cond.span().unwrap_or(self.span), &format!("{}: non-bool condition", env.resolve(&cond))))
+        }
+
+        let body      = self.body.get_type(sym, env, val)?;
+        let else_body = self.else_body.get_type(sym, env, val)?;
+
+        if env.unify(&body, &else_body).is_err() {
+            return Err(RunError::new_pos(self.span, &format!("({}, {}): mismatched if branches", env.resolve(&body), env.resolve(&else_body))))
+        }
+
+        match body {
+            Type::Any | Type::Undefined => Ok(else_body),
+            b                           => Ok(b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Some(Box<Pattern>),
+    None,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(Rc<String>),
+    Char(char),
+    Array(Vec<Pattern>),
+    Identifier(Rc<String>),
+    // a union case, `Rect (w, h)`
+    Variant(Rc<String>, Vec<Pattern>),
+    Wildcard,
+}
+
+impl Pattern {
+    // binds names into `names`/`values`, returns false if the value doesn't fit
+    pub fn matches(&self, value: &Value, names: &mut Vec<Rc<String>>, values: &mut Vec<Value>) -> bool {
+        match (self, value) {
+            (Pattern::Wildcard, _) => true,
+
+            (Pattern::Identifier(name), v) => {
+                names.push(name.clone());
+                values.push(v.clone());
+                true
             },
-            
-            Operand::LtEqual => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Bool),
-                (Type::Str, Type::Str)       => Ok(Type::Bool),
-                (Type::Char, Type::Char)     => Ok(Type::Bool),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}<={:?}): failed to compare", a, b)))
+
+            (Pattern::Some(p), Value::Option(Some(v))) => p.matches(v, names, values),
+            (Pattern::None, Value::Option(None))       => true,
+
+            (Pattern::Int(a), Value::Int(b))         => a == b,
+            (Pattern::Float(a), Value::Float(b))     => a == b,
+            (Pattern::Bool(a), Value::Bool(b))       => a == b,
+            (Pattern::Str(a), Value::Str(b))         => a == b,
+            (Pattern::Char(a), Value::Char(b))       => a == b,
+
+            (Pattern::Array(patterns), Value::Array(content)) => {
+                patterns.len() == content.len() && patterns.iter().zip(content.iter()).all(|(p, v)| p.matches(v, names, values))
+            },
+
+            (Pattern::Variant(tag, patterns), Value::Variant(t, payload)) => {
+                tag == t && patterns.len() == payload.len() && patterns.iter().zip(payload.iter()).all(|(p, v)| p.matches(v, names, values))
+            },
+
+            _ => false,
+        }
+    }
+
+    // names bound by the pattern with their types, checking the pattern fits `t`
+    pub fn bindings(&self, t: &Type, env: &TypeTab, names: &mut Vec<Rc<String>>, types: &mut Vec<Type>) -> RunResult<()> {
+        let literal = |expected: Type| env.unify(&expected, t).map_err(|_| {
+            RunError::new(&format!("{:?}: pattern can't match {}", self, env.resolve(t)))
+        });
+
+        match (self, env.resolve(t)) {
+            (Pattern::Wildcard, _) => Ok(()),
+
+            (Pattern::Identifier(name), _) => {
+                names.push(name.clone());
+                types.push(t.clone());
+                Ok(())
+            },
+
+            (Pattern::Array(patterns), Type::Array(content)) => {
+                if patterns.len() != content.len() {
+                    return Err(RunError::new(&format!("{:?}: pattern can't match {}", self, env.resolve(t))))
+                }
+
+                for (p, t) in patterns.iter().zip(content.iter()) {
+                    p.bindings(t, env, names, types)?
+                }
+
+                Ok(())
+            },
+
+            // the pattern decides the shape of an array that isn't known yet
+            (Pattern::Array(patterns), Type::Var(_)) => {
+                let content: Vec<Rc<Type>> = patterns.iter().map(|_| Rc::new(env.fresh())).collect();
+
+                env.unify(t, &Type::Array(content.clone()))?;
+
+                for (p, t) in patterns.iter().zip(content.iter()) {
+                    p.bindings(t, env, names, types)?
+                }
+
+                Ok(())
+            },
+
+            (Pattern::Array(patterns), Type::List(element)) => {
+                for p in patterns {
+                    p.bindings(&element, env, names, types)?
+                }
+
+                Ok(())
+            },
+
+            (Pattern::Array(patterns), Type::Any) | (Pattern::Array(patterns), Type::Undefined) => {
+                for p in patterns {
+                    p.bindings(t, env, names, types)?
+                }
+
+                Ok(())
+            },
+
+            (Pattern::Some(p), Type::Option(t)) => p.bindings(&t, env, names, types),
+
+            (Pattern::Some(p), Type::Var(_)) => {
+                let inner = env.fresh();
+
+                env.unify(t, &Type::Option(Box::new(inner.clone())))?;
+                p.bindings(&inner, env, names, types)
             },
 
-            Operand::GtEqual => match (self.left.get_type(sym, env, val)?, self.right.get_type(sym, env, val)?) {
-                (Type::Number, Type::Number) => Ok(Type::Bool),
-                (Type::Str, Type::Str)       => Ok(Type::Bool),
-                (Type::Char, Type::Char)     => Ok(Type::Bool),
-                (Type::Any, Type::Any)       => Ok(Type::Any),
-                (a, b) => Err(RunError::new(&format!("({:?}>={:?}): failed to compare", a, b)))
+            (Pattern::Some(p), Type::Any) | (Pattern::Some(p), Type::Undefined) => p.bindings(t, env, names, types),
+
+            (Pattern::None, _) => {
+                let inner = env.fresh();
+                literal(Type::Option(Box::new(inner)))
+            },
+
+            (Pattern::Variant(tag, patterns), _) => {
+                let constructor = match env.constructor(tag) {
+                    Some(c) => c,
+                    None    => return Err(RunError::new(&format!("{}: undeclared constructor", tag))),
+                };
+
+                literal(Type::Union(constructor.union.clone()))?;
+
+                if patterns.len() != constructor.fields.len() {
+                    return Err(RunError::new(&format!("{}: expected {} field(s), found {}", tag, constructor.fields.len(), patterns.len())))
+                }
+
+                for (p, t) in patterns.iter().zip(constructor.fields.iter()) {
+                    p.bindings(t, env, names, types)?
+                }
+
+                Ok(())
             },
 
-            _ => Ok(Type::Undefined),
+            (Pattern::Int(_), _)    => literal(Type::Int),
+            (Pattern::Float(_), _)  => literal(Type::Float),
+            (Pattern::Bool(_), _)   => literal(Type::Bool),
+            (Pattern::Str(_), _)    => literal(Type::Str),
+            (Pattern::Char(_), _)   => literal(Type::Char),
+
+            _ => Err(RunError::new(&format!("{:?}: pattern can't match {}", self, env.resolve(t)))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub body:    Rc<Expression>,
+}
+
+impl Arm {
+    // fresh frame holding the names bound by the pattern
+    fn scope(&self, t: &Type, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<(Rc<SymTab>, Rc<TypeTab>, Rc<ValTab>)> {
+        let mut names = Vec::new();
+        let mut types = Vec::new();
+
+        self.pattern.bindings(t, env, &mut names, &mut types)?;
+
+        let local_val = Rc::new(ValTab::new(val.clone(), &vec![Value::Nil; names.len()]));
+        let local_sym = Rc::new(SymTab::new(sym.clone(), &names));
+        let local_env = Rc::new(TypeTab::new(env.clone(), &types));
+
+        Ok((local_sym, local_env, local_val))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub expr: Rc<Expression>,
+    pub arms: Vec<Arm>,
+    pub span: Span,
+}
+
+impl Visitor for Match {
+    fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
+        self.expr.visit(sym, env, val)?;
+
+        let t = self.expr.get_type(sym, env, val)?;
+
+        for arm in &self.arms {
+            let (local_sym, local_env, local_val) = arm.scope(&t, sym, env, val).map_err(|e| e.or_span(self.span))?;
+            arm.body.visit(&local_sym, &local_env, &local_val)?
+        }
+
+        Ok(())
+    }
+}
+
+impl Evaluator for Match {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        self.eval_tail(sym, env)?.run(env)
+    }
+}
+
+impl Match {
+    // the body of the matching arm is in tail position
+    fn eval_tail(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Tail> {
+        let value = self.expr.eval(sym, env)?;
+
+        for arm in &self.arms {
+            let mut names  = Vec::new();
+            let mut values = Vec::new();
+
+            if arm.pattern.matches(&value, &mut names, &mut values) {
+                let local_sym = Rc::new(SymTab::new(sym.clone(), &names));
+                let local_env = Rc::new(ValTab::new(env.clone(), &values));
+
+                return arm.body.eval_tail(&local_sym, &local_env)
+            }
+        }
+
+        Err(RunError::new_pos(self.span, &format!("{:?}: no matching arm", value)))
+    }
+}
+
+impl Typer for Match {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let t = self.expr.get_type(sym, env, val)?;
+
+        let result = env.fresh();
+
+        for arm in &self.arms {
+            let (local_sym, local_env, local_val) = arm.scope(&t, sym, env, val).map_err(|e| e.or_span(self.span))?;
+
+            let body = arm.body.get_type(&local_sym, &local_env, &local_val)?;
+
+            if env.unify(&result, &body).is_err() {
+                return Err(RunError::new_pos(self.span, &format!("({}, {}): mismatched match arms", env.resolve(&result), env.resolve(&body))))
+            }
+        }
+
+        self.exhaustive(&t, env)?;
+
+        Ok(env.resolve(&result))
+    }
+}
+
+impl Match {
+    // a match on a union has to cover each of its constructors, other matches fail when run instead
+    fn exhaustive(&self, t: &Type, env: &TypeTab) -> RunResult<()> {
+        let mut missing = match env.resolve(t) {
+            Type::Union(ref name) => env.union(name).unwrap_or_default(),
+            _                     => return Ok(()),
+        };
+
+        for arm in &self.arms {
+            match arm.pattern {
+                Pattern::Wildcard | Pattern::Identifier(_) => return Ok(()),
+
+                Pattern::Variant(ref tag, ref patterns) if patterns.iter().all(|p| matches!(*p, Pattern::Wildcard | Pattern::Identifier(_))) => {
+                    missing.retain(|c| c != tag)
+                },
+
+                _ => (),
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            let missing: Vec<&str> = missing.iter().map(|c| c.as_str()).collect();
+            Err(RunError::new_pos(self.span, &format!("non-exhaustive match, missing {}", missing.join(", "))))
         }
     }
 }
 
+// a type written in the source, like the `i64` in `let x: i64 = 10`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub t:    Type,
+    pub span: Span,
+}
+
+impl Annotation {
+    pub fn check(&self, env: &TypeTab, found: &Type) -> RunResult<()> {
+        env.unify(&self.t, found).map_err(|_| {
+            RunError::new_pos(self.span, &format!("mismatched types: expected {}, found {}", self.t, env.resolve(found)))
+        })
+    }
+}
+
+// parameter types, taken from their annotations or left to inference
+fn param_types(annotations: &[Option<Annotation>], env: &TypeTab) -> Vec<Type> {
+    annotations.iter().map(|a| match *a {
+        Some(ref a) => a.t.clone(),
+        None        => env.fresh(),
+    }).collect()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Lambda {
-    pub params: Vec<Rc<String>>,
-    pub body:   Rc<Expression>,
+    pub params:      Vec<Rc<String>>,
+    pub param_types: Vec<Option<Annotation>>,
+    pub body:        Rc<Expression>,
+    pub span:        Span,
 }
 
 impl Evaluator for Lambda {
-    fn eval(&self, _: &Rc<SymTab>, _: &Rc<ValTab>) -> RunResult<Value> {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
         let body = match *self.body {
             Expression::Block(ref s) => s.clone(),
             ref e => vec![Statement::Expression(Rc::new(e.clone()))],
@@ -343,75 +1079,120 @@ impl Evaluator for Lambda {
             Value::Function(
                 self.params.clone(),
                 body,
+                sym.snapshot(),
+                env.clone(),
             )
         )
     }
 }
 
+impl Typer for Lambda {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let params = param_types(&self.param_types, env);
+
+        let local_sym = Rc::new(SymTab::new(sym.clone(), &self.params));
+        let local_env = Rc::new(TypeTab::new(env.clone(), &params));
+
+        let body = self.body.get_type(&local_sym, &local_env, val)?;
+
+        Ok(Type::Function(params, Box::new(body)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Call {
     pub callee: Rc<Expression>,
     pub args:   Vec<Rc<Expression>>,
+    pub span:   Span,
 }
 
 impl Visitor for Call {
     fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
         self.callee.visit(sym, env, val)?;
 
-        match self.callee.eval(sym, val)? {
-            Value::Function(params, body) => {
-
-                let local_sym = Rc::new(SymTab::new(sym.clone(), &params));
+        for arg in self.args.iter() {
+            arg.visit(sym, env, val)?
+        }
 
-                let mut arg_vals  = Vec::new();
-                let mut arg_types = Vec::new();
+        Ok(())
+    }
+}
 
-                for a in self.args.clone() {
-                    arg_vals.push(a.eval(sym, val)?);
-                    arg_types.push(a.get_type(sym, env, val)?)
-                }
+impl Typer for Call {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let callee = self.callee.get_type(sym, env, val)?;
 
-                let local_env = Rc::new(TypeTab::new(env.clone(), &arg_types));
-                let local_val = Rc::new(ValTab::new(val.clone(), &arg_vals));
+        let mut args = Vec::new();
 
-                Expression::Block(body).visit(&local_sym, &local_env, &local_val)?;
-            },
-            _ => (),
+        for a in self.args.iter() {
+            args.push(a.get_type(sym, env, val)?)
         }
 
-        for arg in self.args.iter() {
-            arg.visit(sym, env, val)?
+        let callee_type = env.resolve(&callee);
+
+        match callee_type {
+            Type::Function(..) | Type::Var(_) | Type::Any | Type::Undefined => (),
+            ref t => return val.or_any(RunError::new_pos(self.span, &format!("{}: can't call a non-function", t))),
         }
 
-        Ok(())
+        let ret = env.fresh();
+
+        env.unify(&callee, &Type::Function(args, Box::new(ret.clone()))).map_err(|e| {
+            let called = match *self.callee {
+                Expression::Identifier(ref name, _) => format!("`{}`", name),
+                _                                   => "the function".to_owned(),
+            };
+
+            e.or_span(self.span).with_note(&format!("{} has type {}", called, callee_type))
+        })?;
+
+        Ok(ret)
     }
 }
 
 impl Evaluator for Call {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
-        match self.callee.eval(sym, env)? {
-            Value::Function(params, body) => {
-                let local_sym = Rc::new(SymTab::new(sym.clone(), &params));
-                
-                let mut arg_vals = Vec::new();
-                
-                for a in self.args.clone() {
-                    arg_vals.push(a.eval(sym, env)?)
-                }
-                
-                let local_env = Rc::new(ValTab::new(env.clone(), &arg_vals));
+        self.eval_tail(sym, env)?.run(env)
+    }
+}
 
-                Ok(Expression::Block(body).eval(&local_sym, &local_env)?)
-            },
-            _ => Ok(Value::Nil),
+impl Call {
+    // evaluates the callee and arguments, leaving the call itself to `Tail::run`
+    fn eval_tail(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Tail> {
+        let callee = self.callee.eval(sym, env)?;
+
+        let mut args = Vec::new();
+
+        for a in self.args.iter() {
+            args.push(a.eval(sym, env)?)
         }
+
+        Ok(Tail::Call(callee, args, self.span))
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+// `arr[i]`, or `arr[i]?` which gives an option instead of failing out of bounds
 pub struct Index {
     pub id:    Rc<Expression>,
     pub index: Rc<Expression>,
+    pub safe:  bool,
+    pub span:  Span,
+}
+
+// position of index `n` into `len` elements, negative indices count from the end
+fn element(n: i64, len: usize) -> Option<usize> {
+    let i = if n < 0 {
+        len as i64 + n
+    } else {
+        n
+    };
+
+    if i >= 0 && (i as usize) < len {
+        Some(i as usize)
+    } else {
+        None
+    }
 }
 
 impl Visitor for Index {
@@ -425,23 +1206,211 @@ impl Evaluator for Index {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
         match self.id.eval(sym, env)? {
             Value::Array(content) => match self.index.eval(sym, env)? {
-                Value::Number(n) => Ok((&*content.clone().remove(n as usize).clone()).clone()),
-                c => Err(RunError::new(&format!("{:?}: invalid index", c))),
+                Value::Int(n) => match element(n, content.len()) {
+                    Some(i) if self.safe => Ok(Value::Option(Some(content[i].clone()))),
+                    Some(i)              => Ok((*content[i]).clone()),
+                    None if self.safe    => Ok(Value::Option(None)),
+                    None                 => Err(RunError::new_pos(self.span, &format!("index {} out of bounds for length {}", n, content.len()))),
+                },
+                c => Err(RunError::new_pos(self.span, &format!("{:?}: invalid index", c))),
             },
-            _ => Ok(Value::Nil)
+            c => env.or_nil(RunError::new_pos(self.span, &format!("{}: can't index", c.kind()))),
         }
     }
 }
 
 impl Typer for Index {
     fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
-        match self.id.get_type(sym, env, val)? {
-            Type::Array(content) => match self.index.eval(sym, val)? {
-                Value::Number(n) => Ok((&*content.clone().remove(n as usize).clone()).clone()),
-                c => Err(RunError::new(&format!("{:?}: invalid index", c))),
+        let element = self.element_type(sym, env, val)?;
+
+        if self.safe {
+            Ok(Type::Option(Box::new(element)))
+        } else {
+            Ok(element)
+        }
+    }
+}
+
+impl Index {
+    fn element_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let id    = self.id.get_type(sym, env, val)?;
+        let index = self.index.get_type(sym, env, val)?;
+
+        if env.unify(&Type::Int, &index).is_err() {
+            return Err(RunError::new_pos(self.span, &format!("{}: invalid index", env.resolve(&index))))
+        }
+
+        match env.resolve(&id) {
+            Type::Array(content) => match *self.index {
+                // a literal index picks its element, checked against the array's length
+                Expression::Int(n) => match element(n, content.len()) {
+                    Some(i)            => Ok((*content[i]).clone()),
+                    None if self.safe  => Ok(env.fresh()),
+                    None               => Err(RunError::new_pos(self.span, &format!("index {} out of bounds for length {}", n, content.len()))),
+                },
+
+                // a computed index needs every element to have the same type
+                _ => {
+                    let element = env.fresh();
+
+                    for t in content.iter() {
+                        if env.unify(&element, t).is_err() {
+                            return Err(RunError::new_pos(self.span, &format!("{}: can't index mixed array with a computed index", env.resolve(&id))))
+                        }
+                    }
+
+                    Ok(element)
+                },
+            },
+
+            // a list's length isn't known, so any index may be out of bounds at runtime
+            Type::List(t) => Ok(*t),
+
+            // something not known yet is taken to be a list
+            Type::Var(_) => {
+                let element = env.fresh();
+
+                env.unify(&id, &Type::List(Box::new(element.clone()))).map_err(|e| e.or_span(self.span))?;
+
+                Ok(element)
+            },
+
+            Type::Any | Type::Undefined => Ok(Type::Any),
+
+            t => val.or_any(RunError::new_pos(self.span, &format!("{}: can't index", t))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+// `{ name = "x"; age = 3 }`, or `{ r with age = 4 }` which copies `r` with some fields replaced
+pub struct Record {
+    pub base:   Option<Rc<Expression>>,
+    pub fields: Vec<(Rc<String>, Rc<Expression>)>,
+    pub span:   Span,
+}
+
+impl Visitor for Record {
+    fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
+        if let Some(ref base) = self.base {
+            base.visit(sym, env, val)?
+        }
+
+        for (_, e) in self.fields.iter() {
+            e.visit(sym, env, val)?
+        }
+
+        Ok(())
+    }
+}
+
+impl Evaluator for Record {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        let mut record = match self.base {
+            Some(ref base) => match base.eval(sym, env)? {
+                Value::Record(fields) => fields,
+                c                     => return env.or_nil(RunError::new_pos(self.span, &format!("{}: can't update a non-record", c.kind()))),
+            },
+            None => BTreeMap::new(),
+        };
+
+        for (name, e) in self.fields.iter() {
+            let value = e.eval(sym, env)?;
+
+            if self.base.is_some() && !record.contains_key(name) {
+                return Err(RunError::new_pos(self.span, &format!("{}: no such field", name)))
+            }
+
+            record.insert(name.clone(), Rc::new(value));
+        }
+
+        env.context().allocate(array_size(record.len()))?;
+
+        Ok(Value::Record(record))
+    }
+}
+
+impl Typer for Record {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        let mut types = Vec::new();
+
+        for (name, e) in self.fields.iter() {
+            types.push((name.clone(), e.get_type(sym, env, val)?))
+        }
+
+        let base = match self.base {
+            Some(ref base) => base.get_type(sym, env, val)?,
+            None           => return Ok(Type::Record(types.into_iter().collect())),
+        };
+
+        // an update keeps the type of what it copies, so the fields must exist with the same types
+        match env.resolve(&base) {
+            Type::Record(fields) => {
+                for (name, t) in types.iter() {
+                    match fields.get(name) {
+                        Some(field) => env.unify(field, t).map_err(|e| e.or_span(self.span))?,
+                        None        => return Err(RunError::new_pos(self.span, &format!("{}: no such field in {}", name, Type::Record(fields.clone())))),
+                    }
+                }
+
+                Ok(Type::Record(fields))
+            },
+
+            // a record not known yet needs at least the updated fields
+            t @ Type::Var(_) | t @ Type::Row(..) => {
+                env.unify(&t, &env.row(types.into_iter().collect())).map_err(|e| e.or_span(self.span))?;
+
+                Ok(env.resolve(&t))
+            },
+
+            Type::Any | Type::Undefined => Ok(Type::Any),
+
+            t => val.or_any(RunError::new_pos(self.span, &format!("{}: can't update a non-record", t))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+// `r.name`
+pub struct Field {
+    pub record: Rc<Expression>,
+    pub name:   Rc<String>,
+    pub span:   Span,
+}
+
+impl Evaluator for Field {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        match self.record.eval(sym, env)? {
+            Value::Record(fields) => match fields.get(&self.name) {
+                Some(v) => Ok((**v).clone()),
+                None    => Err(RunError::new_pos(self.span, &format!("{}: no such field", self.name))),
+            },
+            c => env.or_nil(RunError::new_pos(self.span, &format!("{}: can't access a field", c.kind()))),
+        }
+    }
+}
+
+impl Typer for Field {
+    fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<Type> {
+        match env.resolve(&self.record.get_type(sym, env, val)?) {
+            Type::Record(fields) => match fields.get(&self.name) {
+                Some(t) => Ok(t.clone()),
+                None    => Err(RunError::new_pos(self.span, &format!("{}: no such field in {}", self.name, Type::Record(fields.clone())))),
+            },
+
+            // a record not known yet, like a parameter's, needs at least this field
+            t @ Type::Var(_) | t @ Type::Row(..) => {
+                let field = env.fresh();
+                let row   = env.row(vec![(self.name.clone(), field.clone())].into_iter().collect());
+
+                env.unify(&t, &row).map_err(|e| e.or_span(self.span))?;
+
+                Ok(field)
             },
-            Type::Any => Ok(Type::Any),
-            _ => Err(RunError::new(&format!("{:?}: can't index", self.id))),
+
+            Type::Any | Type::Undefined => Ok(Type::Any),
+
+            t => val.or_any(RunError::new_pos(self.span, &format!("{}: can't access a field", t))),
         }
     }
 }
@@ -451,7 +1420,22 @@ pub enum Statement {
     Expression(Rc<Expression>),
     Binding(Binding),
     Function(Function),
+    Recursive(Recursive),
     Assignment(Assignment),
+    TypeDecl(TypeDecl),
+}
+
+impl Statement {
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            Statement::Expression(ref e)          => e.span(),
+            Statement::Binding(ref binding)       => Some(binding.span),
+            Statement::Function(ref function)     => Some(function.span),
+            Statement::Recursive(ref recursive)   => Some(recursive.span),
+            Statement::Assignment(ref assignment) => Some(assignment.span),
+            Statement::TypeDecl(ref decl)         => Some(decl.span),
+        }
+    }
 }
 
 impl Visitor for Statement {
@@ -460,6 +1444,8 @@ impl Visitor for Statement {
             Statement::Expression(ref e)      => e.visit(sym, env, val),
             Statement::Binding(ref binding)   => binding.visit(sym, env, val),
             Statement::Function(ref function) => function.visit(sym, env, val),
+            Statement::Recursive(ref rec)     => rec.visit(sym, env, val),
+            Statement::TypeDecl(ref decl)     => decl.visit(sym, env, val),
             _ => Ok(()),
         }
     }
@@ -471,6 +1457,8 @@ impl Evaluator for Statement {
             Statement::Expression(ref e)      => e.eval(sym, env),
             Statement::Binding(ref binding)   => binding.eval(sym, env),
             Statement::Function(ref function) => function.eval(sym, env),
+            Statement::Recursive(ref rec)     => rec.eval(sym, env),
+            Statement::TypeDecl(ref decl)     => decl.eval(sym, env),
             _ => Ok(Value::Nil),
         }
     }
@@ -487,27 +1475,36 @@ impl Typer for Statement {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Binding {
-    pub left:  Rc<Expression>,
-    pub right: Rc<Expression>,
+    pub left:       Rc<Expression>,
+    pub annotation: Option<Annotation>,
+    pub right:      Rc<Expression>,
+    pub span:       Span,
 }
 
 impl Visitor for Binding {
     fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
         match *self.left {
-            Expression::Identifier(ref name) => {
-                let index = sym.add_name(name);
-                if index >= env.size() {
+            Expression::Identifier(ref name, _) => {
+                let t = self.right.get_type(sym, env, val)?;
+
+                if let Some(ref annotation) = self.annotation {
+                    annotation.check(env, &t)?
+                }
+
+                // declared after the right side, which still sees any earlier binding of the name
+                let index = sym.declare(name);
+                while index >= env.size() {
                     env.grow();
                 }
 
-                if let Err(e) = env.set_type(index, 0, self.right.get_type(sym, env, val)?) {
-                    Err(RunError::new(&format!("{}: error setting type", e)))
+                if let Err(e) = env.set_type(index, 0, env.generalize(&t)) {
+                    Err(RunError::new_pos(self.span, &format!("{}: error setting type", e)))
                 } else {
                     Ok(())
                 }
             }
             
-            ref e => Err(RunError::new(&format!("{:?}: unexpected binding", e)))
+            ref e => Err(RunError::new_pos(self.span, &format!("{:?}: unexpected binding", e)))
         }
     }
 }
@@ -515,14 +1512,16 @@ impl Visitor for Binding {
 impl Evaluator for Binding {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
         match *self.left {
-            Expression::Identifier(ref name) => {
-                let index = sym.add_name(name);
-                if index >= env.size() {
+            Expression::Identifier(ref name, _) => {
+                let value = self.right.eval(sym, env)?;
+
+                let index = sym.bind(name);
+                while index >= env.size() {
                     env.grow();
                 }
 
-                if let Err(e) = env.set_value(index, 0, self.right.eval(sym, env)?) {
-                    Err(RunError::new(&format!("{}: error setting value", e)))
+                if let Err(e) = env.set_value(index, 0, value) {
+                    Err(RunError::new_pos(self.span, &format!("{}: error setting value", e)))
                 } else {
                     Ok(Value::Nil)
                 }
@@ -535,23 +1534,99 @@ impl Evaluator for Binding {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function {
-    pub name:   Rc<String>,
-    pub params: Vec<Rc<String>>,
-    pub body:   Rc<Expression>,
+    pub name:        Rc<String>,
+    pub params:      Vec<Rc<String>>,
+    pub param_types: Vec<Option<Annotation>>,
+    pub return_type: Option<Annotation>,
+    pub body:        Rc<Expression>,
+    pub span:        Span,
+}
+
+impl Function {
+    // gives the function a monomorphic type in its slot, for calls made before it's generalized
+    fn declare(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>) -> RunResult<(usize, Type)> {
+        let index = sym.declare(&self.name);
+        while index >= env.size() {
+            env.grow();
+        }
+
+        let ret = match self.return_type {
+            Some(ref a) => a.t.clone(),
+            None        => env.fresh(),
+        };
+
+        let t = Type::Function(param_types(&self.param_types, env), Box::new(ret));
+
+        env.set_type(index, 0, t.clone())?;
+
+        Ok((index, t))
+    }
+
+    // types the body against the declared type `t`
+    fn infer(&self, t: &Type, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
+        let (params, ret) = match *t {
+            Type::Function(ref params, ref ret) => (params, ret),
+            _                                   => unreachable!(),
+        };
+
+        let local_sym = Rc::new(SymTab::new(sym.clone(), &self.params));
+        let local_env = Rc::new(TypeTab::new(env.clone(), params));
+
+        let body = self.body.get_type(&local_sym, &local_env, val)?;
+
+        if let Some(ref annotation) = self.return_type {
+            annotation.check(env, &body)?
+        }
+
+        env.unify(ret, &body).map_err(|e| e.or_span(self.span))
+    }
+}
+
+// generalizes the types of functions declared together, once none of them hold their monomorphic type
+fn generalize_functions(declared: &[(usize, Type)], span: Span, env: &Rc<TypeTab>) -> RunResult<()> {
+    for &(index, _) in declared {
+        env.set_type(index, 0, Type::Undefined)?;
+    }
+
+    for &(index, ref t) in declared {
+        if let Err(e) = env.set_type(index, 0, env.generalize(t)) {
+            return Err(RunError::new_pos(span, &format!("{}: error setting type", e)))
+        }
+    }
+
+    Ok(())
 }
 
 impl Visitor for Function {
     fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
-        let index = sym.add_name(&self.name);
-        if index >= env.size() {
+        // recursive calls inside the body see the function without generalization
+        let (index, t) = self.declare(sym, env)?;
+
+        self.infer(&t, sym, env, val)?;
+
+        generalize_functions(&[(index, t)], self.span, env)
+    }
+}
+
+impl Function {
+    fn bind(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> usize {
+        let index = sym.bind(&self.name);
+        while index >= env.size() {
             env.grow();
         }
 
-        let local_sym = Rc::new(SymTab::new(sym.clone(), &self.params));
-        let local_env = Rc::new(TypeTab::new(env.clone(), &self.params.iter().map(|_| Type::Any).collect()));
+        index
+    }
 
-        if let Err(e) = env.set_type(index, 0, self.body.get_type(&local_sym, &local_env, val)?) {
-            Err(RunError::new(&format!("{}: error setting type", e)))
+    // the function in slot `index`, closing over the names in `captured`
+    fn define(&self, index: usize, captured: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<()> {
+        let body = match *self.body {
+            Expression::Block(ref s) => s.clone(),
+            ref e => vec![Statement::Expression(Rc::new(e.clone()))],
+        };
+
+        if let Err(e) = env.set_value(index, 0, Value::Function(self.params.clone(), body, captured.clone(), env.clone())) {
+            Err(RunError::new_pos(self.span, &format!("{}: error setting value", e)))
         } else {
             Ok(())
         }
@@ -560,21 +1635,127 @@ impl Visitor for Function {
 
 impl Evaluator for Function {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
-        let body = match *self.body {
-            Expression::Block(ref s) => s.clone(),
-            ref e => vec![Statement::Expression(Rc::new(e.clone()))],
-        };
+        // bound before capturing, so the body can call itself
+        let index = self.bind(sym, env);
 
-        let index = sym.add_name(&self.name);
-        if index >= env.size() {
-            env.grow();
+        self.define(index, &sym.snapshot(), env)?;
+
+        Ok(Value::Nil)
+    }
+}
+
+// `let rec f x = ... and g y = ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recursive {
+    pub functions: Vec<Function>,
+    pub span:      Span,
+}
+
+impl Visitor for Recursive {
+    fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, val: &Rc<ValTab>) -> RunResult<()> {
+        // every name is declared before any body is typed, so they can refer to each other
+        let mut declared = Vec::new();
+
+        for function in self.functions.iter() {
+            declared.push(function.declare(sym, env)?)
+        }
+
+        for (function, (_, t)) in self.functions.iter().zip(declared.iter()) {
+            function.infer(t, sym, env, val)?
+        }
+
+        generalize_functions(&declared, self.span, env)
+    }
+}
+
+impl Evaluator for Recursive {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        // every name is bound before any is captured, so the bodies can call each other
+        let indices: Vec<usize> = self.functions.iter().map(|f| f.bind(sym, env)).collect();
+
+        let captured = sym.snapshot();
+
+        for (function, index) in self.functions.iter().zip(indices) {
+            function.define(index, &captured, env)?;
         }
 
-        if let Err(e) = env.set_value(index, 0, Value::Function(self.params.clone(), body)) {
-            Err(RunError::new(&format!("{}: error setting value", e)))
+        Ok(Value::Nil)
+    }
+}
+
+// `type Shape = | Circle of f64 | Rect of f64 * f64`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDecl {
+    pub name:         Rc<String>,
+    pub constructors: Vec<(Rc<String>, Vec<Type>)>,
+    pub span:         Span,
+}
+
+impl TypeDecl {
+    // constructors taking fields are functions, the rest are values of the union
+    fn constructor_type(&self, fields: &[Type]) -> Type {
+        let union = Type::Union(self.name.clone());
+
+        if fields.is_empty() {
+            union
         } else {
-            Ok(Value::Nil)
+            Type::Function(fields.to_vec(), Box::new(union))
+        }
+    }
+}
+
+impl Visitor for TypeDecl {
+    fn visit(&self, sym: &Rc<SymTab>, env: &Rc<TypeTab>, _val: &Rc<ValTab>) -> RunResult<()> {
+        for (_, fields) in &self.constructors {
+            for t in fields {
+                if let Type::Union(ref name) = *t {
+                    if *name != self.name && env.union(name).is_none() {
+                        return Err(RunError::new_pos(self.span, &format!("{}: undeclared type", name)))
+                    }
+                }
+            }
+        }
+
+        env.declare_union(&self.name, &self.constructors);
+
+        for (name, fields) in &self.constructors {
+            let index = sym.declare(name);
+            while index >= env.size() {
+                env.grow();
+            }
+
+            if let Err(e) = env.set_type(index, 0, self.constructor_type(fields)) {
+                return Err(RunError::new_pos(self.span, &format!("{}: error setting type", e)))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Evaluator for TypeDecl {
+    fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
+        for (name, fields) in &self.constructors {
+            let index = sym.bind(name);
+            while index >= env.size() {
+                env.grow();
+            }
+
+            let tag   = name.clone();
+            let value = if fields.is_empty() {
+                Value::Variant(tag, Vec::new())
+            } else {
+                Value::Native(Native::new(name, fields.len(), Some(self.constructor_type(fields)), move |args| {
+                    Ok(Value::Variant(tag.clone(), args.iter().cloned().map(Rc::new).collect()))
+                }))
+            };
+
+            if let Err(e) = env.set_value(index, 0, value) {
+                return Err(RunError::new_pos(self.span, &format!("{}: error setting value", e)))
+            }
         }
+
+        Ok(Value::Nil)
     }
 }
 
@@ -582,19 +1763,20 @@ impl Evaluator for Function {
 pub struct Assignment {
     pub left:  Rc<Expression>,
     pub right: Rc<Expression>,
+    pub span:  Span,
 }
 
 impl Evaluator for Assignment {
     fn eval(&self, sym: &Rc<SymTab>, env: &Rc<ValTab>) -> RunResult<Value> {
         match *self.left {
-            Expression::Identifier(ref name) => {
-                let (a, b) = match sym.get_name(name) {
+            Expression::Identifier(ref name, _) => {
+                let (a, b) = match sym.get_bound(name) {
                     Some((a, b)) => (a, b),
-                    None         => return Err(RunError::new(&format!("{}: undeclared variable", name))),
+                    None         => return Err(RunError::new_pos(self.span, &format!("{}: undeclared variable", name))),
                 };
 
                 if let Err(e) = env.set_value(a, b, self.right.eval(sym, env)?) {
-                    Err(RunError::new(&format!("{}: error setting value", e)))
+                    Err(RunError::new_pos(self.span, &format!("{}: error setting value", e)))
                 } else {
                     Ok(Value::Nil)
                 }
@@ -612,9 +1794,36 @@ pub enum Operand {
     Add, Sub,
     Equal, NEqual,
     Lt, Gt, LtEqual, GtEqual,
+    And,
+    Or,
+    Compose, ComposeBack,
+    Pipe, PipeBack,
 }
 
 impl Operand {
+    pub fn symbol(&self) -> &str {
+        match *self {
+            Operand::Pow         => "^",
+            Operand::Mul         => "*",
+            Operand::Div         => "/",
+            Operand::Mod         => "%",
+            Operand::Add         => "+",
+            Operand::Sub         => "-",
+            Operand::Equal       => "==",
+            Operand::NEqual      => "!=",
+            Operand::Lt          => "<",
+            Operand::Gt          => ">",
+            Operand::LtEqual     => "<=",
+            Operand::GtEqual     => ">=",
+            Operand::And         => " and ",
+            Operand::Or          => " or ",
+            Operand::Compose     => ">>",
+            Operand::ComposeBack => "<<",
+            Operand::Pipe        => "|>",
+            Operand::PipeBack    => "<|",
+        }
+    }
+
     pub fn from_str(v: &str) -> Option<(Operand, u8)> {
         match v {
             "^"   => Some((Operand::Pow, 0)),
@@ -629,7 +1838,28 @@ impl Operand {
             ">"   => Some((Operand::Gt, 4)),
             "<="  => Some((Operand::LtEqual, 4)),
             ">="  => Some((Operand::GtEqual, 4)),
+            "and" => Some((Operand::And, 5)),
+            "or"  => Some((Operand::Or, 6)),
+            ">>"  => Some((Operand::Compose, 7)),
+            "<<"  => Some((Operand::ComposeBack, 7)),
+            "|>"  => Some((Operand::Pipe, 8)),
+            "<|"  => Some((Operand::PipeBack, 8)),
             _     => None,
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperand {
+    Neg,
+    Not,
+}
+
+impl UnaryOperand {
+    pub fn symbol(&self) -> &str {
+        match *self {
+            UnaryOperand::Neg => "-",
+            UnaryOperand::Not => "not ",
+        }
+    }
+}
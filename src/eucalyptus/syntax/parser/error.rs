@@ -1,4 +1,4 @@
-use super::TokenPosition;
+use super::Span;
 
 use std::fmt;
 
@@ -10,7 +10,7 @@ pub enum ParserErrorValue {
 #[derive(Debug)]
 pub struct ParserError {
     value:    ParserErrorValue,
-    position: Option<TokenPosition>,
+    pub span: Option<Span>,
 }
 
 #[allow(dead_code)]
@@ -18,14 +18,20 @@ impl ParserError {
     pub fn new(value: &str) -> ParserError {
         ParserError {
             value: ParserErrorValue::Constant(value.to_owned()),
-            position: None,
+            span:  None,
         }
     }
 
-    pub fn new_pos(position: TokenPosition, value: &str) -> ParserError {
+    pub fn new_pos(span: Span, value: &str) -> ParserError {
         ParserError {
             value: ParserErrorValue::Constant(value.to_owned()),
-            position: Some(position),
+            span:  Some(span),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self.value {
+            ParserErrorValue::Constant(ref s) => s,
         }
     }
 }
@@ -33,9 +39,9 @@ impl ParserError {
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.value {
-            ParserErrorValue::Constant(ref s) => match self.position {
-                Some(p) => write!(f, "{}: {}", p, s),
-                None    => write!(f, "{}", s),
+            ParserErrorValue::Constant(ref s) => match self.span {
+                Some(span) => write!(f, "{}: {}", span, s),
+                None       => write!(f, "{}", s),
             }
         }
     }
@@ -2,6 +2,12 @@ use std::rc::Rc;
 
 use super::*;
 
+// parameter names with their annotations, if any
+type Params = (Vec<Rc<String>>, Vec<Option<Annotation>>);
+
+// call arguments end at compositions and pipes, so `xs |> map f |> g` pipes into `map f`
+const ARGUMENT: u8 = 7;
+
 pub struct Parser {
     traveler: Traveler,
 }
@@ -37,23 +43,67 @@ impl Parser {
     }
     
     fn expression(&mut self) -> ParserResult<Expression> {
+        self.expression_within(u8::MAX)
+    }
+
+    // an expression of operators binding tighter than `limit`
+    fn expression_within(&mut self, limit: u8) -> ParserResult<Expression> {
         self.skip_whitespace()?;
 
-        let expr = self.term()?;
+        let start = self.traveler.current().position;
+        let expr  = self.term()?;
 
         if expr == Expression::EOF {
             return Ok(expr)
         }
 
         if self.traveler.remaining() > 1 {
+            let span = self.traveler.span_from(start);
+
             self.skip_whitespace()?;
-            if self.traveler.current().token_type == TokenType::Operator {
-                return self.operation(expr)
+            if self.at_operator(limit) {
+                return self.operation(expr, span, limit)
             }
         }
 
         Ok(expr)
     }
+
+    // `and` starting a line begins the next function of a `let rec` instead, and `-` a negated expression
+    fn at_operator(&self, limit: u8) -> bool {
+        let token = self.traveler.current();
+
+        if token.token_type != TokenType::Operator {
+            return false
+        }
+
+        match Operand::from_str(token.content()) {
+            Some((_, precedence)) if precedence < limit => (),
+            _                                           => return false,
+        }
+
+        match token.content().as_str() {
+            "and" | "-" => self.traveler.last_end().line == token.position.line,
+            _           => true,
+        }
+    }
+
+    // the lexer accepts any u64, integers are i64
+    fn int_literal(&self) -> ParserResult<i64> {
+        match self.traveler.current_content().parse::<i64>() {
+            Ok(n)  => Ok(n),
+            Err(_) => Err(ParserError::new_pos(self.traveler.current().span(), &format!("integer literal too large: {}", self.traveler.current_content()))),
+        }
+    }
+
+    fn spanned_term(&mut self) -> ParserResult<(Expression, Span)> {
+        self.skip_whitespace()?;
+
+        let start = self.traveler.current().position;
+        let term  = self.term()?;
+
+        Ok((term, self.traveler.span_from(start)))
+    }
     
     pub fn term(&mut self) -> ParserResult<Expression> {
         self.skip_whitespace()?;
@@ -64,13 +114,13 @@ impl Parser {
 
         match self.traveler.current().token_type {
             TokenType::IntLiteral    => {
-                let a = Ok(Expression::Number(self.traveler.current_content().parse::<f64>().unwrap()));
+                let a = Ok(Expression::Int(self.int_literal()?));
                 self.traveler.next();
                 a
             }
 
             TokenType::FloatLiteral  => {
-                let a = Ok(Expression::Number(self.traveler.current_content().parse::<f64>().unwrap()));
+                let a = Ok(Expression::Float(self.traveler.current_content().parse::<f64>().unwrap()));
                 self.traveler.next();
                 a
             }
@@ -82,26 +132,29 @@ impl Parser {
             }
 
             TokenType::StringLiteral => {
-                let a = Ok(Expression::Str(Rc::new(self.traveler.current_content().clone())));
+                let a = Ok(Expression::Str(Rc::new(self.traveler.current().content().clone())));
                 self.traveler.next();
                 a
             }
             
             TokenType::CharLiteral => {
-                let a = Ok(Expression::Char(self.traveler.current_content().clone().remove(0)));
+                let a = Ok(Expression::Char(self.traveler.current().content().clone().remove(0)));
                 self.traveler.next();
                 a
             }
 
             TokenType::Identifier => {
-                let a = Expression::Identifier(Rc::new(self.traveler.current_content().clone()));
+                let start = self.traveler.current().position;
+
+                let a = Expression::Identifier(Rc::new(self.traveler.current_content().clone()), self.traveler.current().span());
                 self.traveler.next();
 
                 if self.traveler.remaining() > 1 {
                     match self.traveler.current_content().as_str() {
-                        "}" | "]" | "," | ")" => Ok(a),
-                        "["                   => self.index(Rc::new(a)),
-                        _                     => self.try_call(a),
+                        "}" | "]" | "," | ")" | "|" => Ok(a),
+                        "["                         => self.index(Rc::new(a), start),
+                        "."                         => self.field(a, start),
+                        _                           => self.try_call(a, start),
                     }
                 } else {
                     Ok(a)
@@ -110,9 +163,11 @@ impl Parser {
 
             TokenType::Symbol => match self.traveler.current_content().as_str() {
                 "(" => {
+                    let start = self.traveler.current().position;
+
                     self.traveler.next();
                     if self.traveler.current_content() == ")" {
-                        return Err(ParserError::new_pos(self.traveler.current().position, &format!("illegal empty clause '()'")))
+                        return Err(ParserError::new_pos(self.traveler.current().span(), "illegal empty clause '()'"))
                     }
                     
                     let a = self.expression()?;
@@ -122,55 +177,127 @@ impl Parser {
                     self.traveler.next();
 
                     if self.traveler.current_content() == "[" {
-                        self.index(Rc::new(a))
+                        self.index(Rc::new(a), start)
+                    } else if self.traveler.current_content() == "." {
+                        self.field(a, start)
                     } else if self.traveler.remaining() > 1 {
-                        self.try_call(a)
+                        self.try_call(a, start)
                     } else {
                         Ok(a)
                     }
                 }
                 "{" => self.array(),
-                _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected symbol: {}", self.traveler.current_content()))),
+                "!" => self.unary(UnaryOperand::Not),
+                _ => Err(ParserError::new_pos(self.traveler.current().span(), &format!("unexpected symbol: {}", self.traveler.current_content()))),
             },
 
+            TokenType::Operator if self.traveler.current_content() == "-" => self.unary(UnaryOperand::Neg),
+
             TokenType::Keyword => match self.traveler.current_content().as_str() {
-                "fun" => self.lambda(),
-                _     => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected keyword: {}", self.traveler.current_content()))),
+                "fun"   => self.lambda(),
+                "if"    => self.if_expression(),
+                "match" => self.match_expression(),
+                "not"   => self.unary(UnaryOperand::Not),
+                _       => Err(ParserError::new_pos(self.traveler.current().span(), &format!("unexpected keyword: {}", self.traveler.current_content()))),
             },
 
-            _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+            _ => Err(ParserError::new_pos(self.traveler.current().span(), &format!("unexpected: {}", self.traveler.current_content()))),
         }
     }
 
-    fn index(&mut self, id: Rc<Expression>) -> ParserResult<Expression> {
+    fn index(&mut self, id: Rc<Expression>, start: TokenPosition) -> ParserResult<Expression> {
         self.traveler.next();
 
         let index = Rc::new(self.expression()?);
 
         self.traveler.expect_content("]")?;
         self.traveler.next();
-        
+
+        let safe = self.traveler.current_content() == "?";
+
+        if safe {
+            self.traveler.next();
+        }
+
+        let index = Expression::Index(
+            Index {
+                id,
+                index,
+                safe,
+                span: self.traveler.span_from(start),
+            }
+        );
+
+        if self.traveler.current_content() == "." {
+            self.field(index, start)
+        } else {
+            Ok(index)
+        }
+    }
+
+    // `r.name`, `r.a.b`, and what may follow like an index or arguments
+    fn field(&mut self, record: Expression, start: TokenPosition) -> ParserResult<Expression> {
+        let mut field = record;
+
+        while self.traveler.current_content() == "." {
+            self.traveler.next();
+
+            let name = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+            self.traveler.next();
+
+            field = Expression::Field(
+                Field {
+                    record: Rc::new(field),
+                    name,
+                    span:   self.traveler.span_from(start),
+                }
+            )
+        }
+
+        if self.traveler.current_content() == "[" {
+            self.index(Rc::new(field), start)
+        } else if self.traveler.remaining() > 1 {
+            self.try_call(field, start)
+        } else {
+            Ok(field)
+        }
+    }
+    
+    // binds tighter than any operator, `-a * b` is `(-a) * b`
+    fn unary(&mut self, op: UnaryOperand) -> ParserResult<Expression> {
+        let start = self.traveler.current().position;
+        let sign  = self.traveler.current().span();
+        self.traveler.next();
+
+        let expr = self.term()?;
+
+        if expr == Expression::EOF {
+            return Err(ParserError::new_pos(sign, &format!("expected an operand of '{}'", op.symbol().trim())))
+        }
+
         Ok(
-            Expression::Index(
-                Index {
-                    id,
-                    index,
+            Expression::Unary(
+                Unary {
+                    op,
+                    expr: Rc::new(expr),
+                    span: self.traveler.span_from(start),
                 }
             )
         )
     }
-    
-    fn try_call(&mut self, callee: Expression) -> ParserResult<Expression> {
+
+    fn try_call(&mut self, callee: Expression, start: TokenPosition) -> ParserResult<Expression> {
         match self.traveler.current().token_type {
             TokenType::IntLiteral    |
             TokenType::FloatLiteral  |
             TokenType::BoolLiteral   |
             TokenType::StringLiteral |
             TokenType::CharLiteral   |
-            TokenType::Identifier => self.call(callee),
+            TokenType::Identifier => self.call(callee, start),
             TokenType::Symbol     => match self.traveler.current_content().as_str() {
-                "(" | "{"         => self.call(callee),
-                _                 => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected symbol: {}", self.traveler.current_content()))),
+                "(" | "{"                         => self.call(callee, start),
+                "|" | "," | ")" | "}" | "]" | ";" => Ok(callee),
+                _                                 => Err(ParserError::new_pos(self.traveler.current().span(), &format!("unexpected symbol: {}", self.traveler.current_content()))),
             },
             
             _ => Ok(callee),
@@ -178,8 +305,24 @@ impl Parser {
     }
 
     fn array(&mut self) -> ParserResult<Expression> {
+        let start = self.traveler.current().position;
+
         self.traveler.next();
-        
+        self.skip_whitespace()?;
+
+        // `{ name = ..`
+        if self.traveler.current().token_type == TokenType::Identifier {
+            self.traveler.next();
+
+            let field = self.traveler.current().token_type == TokenType::Symbol && self.traveler.current_content() == "=";
+
+            self.traveler.prev();
+
+            if field {
+                return self.record(None, start)
+            }
+        }
+
         let mut content = Vec::new();
         
         let mut acc = 0;
@@ -190,7 +333,15 @@ impl Parser {
                 content.push(Rc::new(self.expression()?));
 
             } else if acc == 0 {
-                content.push(Rc::new(self.expression()?));
+                let first = self.expression()?;
+
+                // `{ r with ..`
+                if self.traveler.current().token_type == TokenType::Keyword && self.traveler.current_content() == "with" {
+                    self.traveler.next();
+                    return self.record(Some(Rc::new(first)), start)
+                }
+
+                content.push(Rc::new(first));
 
             } else {
                 self.traveler.prev();
@@ -206,49 +357,256 @@ impl Parser {
         self.traveler.next();
 
         if self.traveler.current_content() == "[" {
-            self.index(Rc::new(Expression::Array(content)))
+            self.index(Rc::new(Expression::Array(content)), start)
         } else {
             Ok(Expression::Array(content))
         }
     }
     
-    fn lambda(&mut self) -> ParserResult<Expression> {
-        self.traveler.next();
+    // the fields of a record after its `{`, or after the `with` of an update
+    fn record(&mut self, base: Option<Rc<Expression>>, start: TokenPosition) -> ParserResult<Expression> {
+        let mut fields: Vec<(Rc<String>, Rc<Expression>)> = Vec::new();
 
-        let mut params = Vec::new();
+        loop {
+            self.skip_whitespace()?;
 
-        while self.traveler.current_content() != "->" {         
-            let param = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+            if self.traveler.current_content() == "}" {
+                break
+            }
+
+            let position = self.traveler.current().span();
+
+            let name = Rc::new(self.traveler.expect(TokenType::Identifier)?);
             self.traveler.next();
-            
-            params.push(param);
-            
-            if self.traveler.current().token_type == TokenType::EOL || self.traveler.current().token_type == TokenType::EOF {
-                return Err(ParserError::new_pos(self.traveler.current().position, "expected '=' found eol/eof"))
+
+            if fields.iter().any(|(n, _)| *n == name) {
+                return Err(ParserError::new_pos(position, &format!("{}: duplicate field", name)))
+            }
+
+            self.traveler.expect_content("=")?;
+            self.traveler.next();
+
+            fields.push((name, Rc::new(self.expression()?)));
+
+            // fields are separated by `;` or line breaks
+            if self.traveler.current_content() == ";" {
+                self.traveler.next();
+            } else if self.traveler.current_content() != "}" && self.traveler.last_end().line == self.traveler.current().position.line {
+                return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected ';' between fields, found '{}'", self.traveler.current().content())))
             }
         }
-        
+
+        self.traveler.next();
+
+        Ok(
+            Expression::Record(
+                Record {
+                    base,
+                    fields,
+                    span: self.traveler.span_from(start),
+                }
+            )
+        )
+    }
+
+    fn lambda(&mut self) -> ParserResult<Expression> {
+        let start = self.traveler.current().position;
+
+        self.traveler.next();
+
+        let (params, param_types) = self.params(&["->"])?;
+
         self.traveler.next();
         
-        let body;
-        
-        if self.traveler.current_content() == "\n" {
-            body = Rc::new(self.block()?)
+        let body = if self.traveler.current_content() == "\n" {
+            Rc::new(self.block()?)
         } else {
-            body = Rc::new(self.expression()?)
-        }
+            Rc::new(self.expression()?)
+        };
 
         Ok(
             Expression::Lambda(
                 Lambda {
                     params,
+                    param_types,
+                    body,
+                    span: self.traveler.span_from(start),
+                }
+            )
+        )
+    }
+
+    fn if_expression(&mut self) -> ParserResult<Expression> {
+        let start = self.traveler.current().position;
+
+        self.traveler.next();
+
+        let cond = Rc::new(self.expression()?);
+
+        self.skip_whitespace()?;
+        self.traveler.expect_content("then")?;
+        self.traveler.next();
+
+        let body = Rc::new(self.branch()?);
+
+        self.skip_whitespace()?;
+
+        let else_body = match self.traveler.current_content().as_str() {
+            "elif" => self.if_expression()?,
+            "else" => {
+                self.traveler.next();
+                self.branch()?
+            },
+            _ if self.traveler.remaining() < 2 => return Err(ParserError::new_pos(self.traveler.current().span(), "expected 'else', found end of source")),
+            _ => return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected 'else', found '{}'", self.traveler.current_content()))),
+        };
+
+        Ok(
+            Expression::If(
+                If {
+                    cond,
+                    body,
+                    else_body: Rc::new(else_body),
+                    span:      self.traveler.span_from(start),
+                }
+            )
+        )
+    }
+
+    fn match_expression(&mut self) -> ParserResult<Expression> {
+        let start = self.traveler.current().position;
+
+        self.traveler.next();
+
+        let expr = Rc::new(self.expression()?);
+
+        self.skip_whitespace()?;
+        self.traveler.expect_content("with")?;
+        self.traveler.next();
+
+        self.skip_whitespace()?;
+
+        let mut arms = Vec::new();
+
+        while self.traveler.remaining() > 1 && self.traveler.current_content() == "|" {
+            self.traveler.next();
+
+            let pattern = self.pattern()?;
+
+            self.traveler.expect_content("->")?;
+            self.traveler.next();
+
+            let body = Rc::new(self.branch()?);
+
+            arms.push(
+                Arm {
+                    pattern,
                     body,
                 }
+            );
+
+            self.skip_whitespace()?;
+        }
+
+        if arms.is_empty() {
+            return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected '|', found '{}'", self.traveler.current_content())))
+        }
+
+        Ok(
+            Expression::Match(
+                Match {
+                    expr,
+                    arms,
+                    span: self.traveler.span_from(start),
+                }
             )
         )
     }
 
-    fn call(&mut self, caller: Expression) -> ParserResult<Expression> {
+    fn pattern(&mut self) -> ParserResult<Pattern> {
+        let pattern = match self.traveler.current().token_type {
+            TokenType::IntLiteral   => Pattern::Int(self.int_literal()?),
+            TokenType::FloatLiteral => Pattern::Float(self.traveler.current_content().parse::<f64>().unwrap()),
+            TokenType::BoolLiteral   => Pattern::Bool(self.traveler.current_content() == "true"),
+            TokenType::StringLiteral => Pattern::Str(Rc::new(self.traveler.current().content().clone())),
+            TokenType::CharLiteral   => Pattern::Char(self.traveler.current().content().clone().remove(0)),
+
+            TokenType::Identifier => match self.traveler.current_content().as_str() {
+                "_"    => Pattern::Wildcard,
+                "None" => Pattern::None,
+                "Some" => {
+                    self.traveler.next();
+                    return Ok(Pattern::Some(Box::new(self.pattern()?)))
+                },
+                name if name.starts_with(char::is_uppercase) => return self.variant(Rc::new(name.to_owned())),
+                name => Pattern::Identifier(Rc::new(name.to_owned())),
+            },
+
+            TokenType::Symbol if self.traveler.current_content() == "{" => {
+                self.traveler.next();
+
+                let mut content = Vec::new();
+
+                while self.traveler.current_content() != "}" {
+                    content.push(self.pattern()?);
+
+                    match self.traveler.current_content().as_str() {
+                        "," => { self.traveler.next(); },
+                        "}" => (),
+                        c   => return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected ',' or '}}' in pattern, found '{}'", c))),
+                    }
+                }
+
+                Pattern::Array(content)
+            },
+
+            _ => return Err(ParserError::new_pos(self.traveler.current().span(), &format!("unexpected in pattern: {}", self.traveler.current_content()))),
+        };
+
+        self.traveler.next();
+
+        Ok(pattern)
+    }
+
+    // a constructor and its fields, `Red`, `Circle r` or `Rect (w, h)`
+    fn variant(&mut self, tag: Rc<String>) -> ParserResult<Pattern> {
+        self.traveler.next();
+
+        let mut fields = Vec::new();
+
+        if self.traveler.current_content() == "(" {
+            self.traveler.next();
+
+            loop {
+                fields.push(self.pattern()?);
+
+                match self.traveler.current_content().as_str() {
+                    "," => { self.traveler.next(); },
+                    ")" => { self.traveler.next(); break },
+                    c   => return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected ',' or ')' in pattern, found '{}'", c))),
+                }
+            }
+        } else {
+            match self.traveler.current().token_type {
+                TokenType::IntLiteral | TokenType::FloatLiteral | TokenType::BoolLiteral | TokenType::StringLiteral | TokenType::CharLiteral | TokenType::Identifier => fields.push(self.pattern()?),
+                TokenType::Symbol if self.traveler.current_content() == "{" => fields.push(self.pattern()?),
+                _ => (),
+            }
+        }
+
+        Ok(Pattern::Variant(tag, fields))
+    }
+
+    // inline expression, or an indented block on the following lines
+    fn branch(&mut self) -> ParserResult<Expression> {
+        if self.traveler.current().token_type == TokenType::EOL {
+            self.block()
+        } else {
+            self.expression()
+        }
+    }
+
+    fn call(&mut self, caller: Expression, start: TokenPosition) -> ParserResult<Expression> {
         let mut args = Vec::new();
 
         let mut acc = 0;
@@ -257,7 +615,7 @@ impl Parser {
             if self.traveler.current_content() == "," {
                 self.traveler.next();
                 
-                let expr = Rc::new(self.expression()?);
+                let expr = Rc::new(self.expression_within(ARGUMENT)?);
 
                 if *expr == Expression::EOF {
                     break
@@ -266,7 +624,7 @@ impl Parser {
                 args.push(expr);
 
             } else if acc == 0 {
-                let expr = Rc::new(self.expression()?);
+                let expr = Rc::new(self.expression_within(ARGUMENT)?);
                 
                 if *expr == Expression::EOF {
                     break
@@ -290,108 +648,325 @@ impl Parser {
                 Call {
                     callee: Rc::new(caller),
                     args,
+                    span:   self.traveler.span_from(start),
                 }
             )
         )
     }
 
+    // the lines after the current line break indented past the line opening the block
+    //
+    // the block is as deep as its first line and ends before the first line indented less, blank lines
+    // don't end it. its lines are parsed with that depth taken off, so blocks nest the same way inside it
     fn block(&mut self) -> ParserResult<Expression> {
+        let opening   = self.traveler.indentation();
+        let mut depth = None;
         let mut stack = Vec::new();
-        loop {
-            if self.traveler.current().token_type == TokenType::Indent {
-                self.traveler.next();
-                if self.traveler.current_content() == "\n" {
-                    self.traveler.next();
-                    break
-                }
-            } else if self.traveler.current_content() == "\n" {
-                stack.push(self.traveler.current().clone());
-                self.traveler.next();
 
-                if self.traveler.current().token_type == TokenType::Indent {
-                    self.traveler.next();
-                } else {
-                    break
-                }
+        while self.traveler.remaining() > 1 && self.traveler.current().token_type == TokenType::EOL {
+            let eol = self.traveler.current().clone();
+            self.traveler.next();
+
+            let mut indents = Vec::new();
+
+            while self.traveler.remaining() > 1 && self.traveler.current().token_type == TokenType::Indent {
+                indents.push(self.traveler.current().clone());
+                self.traveler.next();
             }
-            
+
             if self.traveler.remaining() < 2 {
                 break
             }
 
-            stack.push(self.traveler.current().clone());
-            self.traveler.next();
-        }        
+            if self.traveler.current().token_type == TokenType::EOL {
+                continue
+            }
 
-        let mut parser = Parser::new(Traveler::new(stack));
+            let level = *depth.get_or_insert(indents.len());
+
+            if indents.len() < level || level <= opening {
+                for _ in 0 .. indents.len() {
+                    self.traveler.prev();
+                }
+
+                break
+            }
 
-        match parser.parse() {
-            Ok(s)    => Ok(Expression::Block(s)),
-            Err(why) => Err(ParserError::new(&format!("{}", why))),
+            stack.push(eol);
+            stack.extend(indents.into_iter().skip(level));
+
+            while self.traveler.remaining() > 1 && self.traveler.current().token_type != TokenType::EOL {
+                stack.push(self.traveler.current().clone());
+                self.traveler.next();
+            }
         }
+
+        let mut parser = Parser::new(Traveler::new(stack));
+
+        parser.parse().map(Expression::Block)
     }
 
-    fn binding(&mut self) -> ParserResult<Statement> {
-        self.traveler.next();
-        
-        let left = Rc::new(self.traveler.expect(TokenType::Identifier)?);
-        self.traveler.next();
-        
-        if self.traveler.current().token_type == TokenType::Identifier {
-            let mut params = Vec::new();
-            while self.traveler.current_content() != "=" {
-                let param = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+    // parameters up to one of `end`, each either a name or an annotated '(name: type)'
+    fn params(&mut self, end: &[&str]) -> ParserResult<Params> {
+        let mut params = Vec::new();
+        let mut types  = Vec::new();
+
+        while !end.contains(&self.traveler.current_content().as_str()) {
+            if self.traveler.current_content() == "(" {
+                self.traveler.next();
+
+                params.push(Rc::new(self.traveler.expect(TokenType::Identifier)?));
+                self.traveler.next();
+
+                self.traveler.expect_content(":")?;
+                types.push(self.annotation()?);
+
+                self.traveler.expect_content(")")?;
+                self.traveler.next();
+            } else {
+                params.push(Rc::new(self.traveler.expect(TokenType::Identifier)?));
+                types.push(None);
                 self.traveler.next();
-                
-                params.push(param);
-                
-                if self.traveler.current().token_type == TokenType::EOL || self.traveler.current().token_type == TokenType::EOF {
-                    return Err(ParserError::new_pos(self.traveler.current().position, "expected '=' found eol/eof"))
-                }
             }
 
-            self.traveler.next();
-            
-            let body;
+            if self.traveler.current().token_type == TokenType::EOL || self.traveler.current().token_type == TokenType::EOF {
+                return Err(ParserError::new_pos(self.traveler.current().span(), &format!("expected '{}' found eol/eof", end[0])))
+            }
+        }
 
-            match self.traveler.current().token_type {
-                TokenType::EOL => {
-                    self.traveler.next();
+        Ok((params, types))
+    }
 
-                    body = Rc::new(self.block()?)
-                },
-                _ => body = Rc::new(self.expression()?)
+    // an optional ': type' annotation
+    fn annotation(&mut self) -> ParserResult<Option<Annotation>> {
+        if self.traveler.current_content() != ":" {
+            return Ok(None)
+        }
+
+        self.traveler.next();
+
+        let span = self.traveler.current().span();
+        let t    = self.type_name()?;
+
+        Ok(Some(Annotation { t, span }))
+    }
+
+    // a builtin type like `f64`, or the name of a declared union
+    fn type_name(&mut self) -> ParserResult<Type> {
+        let span = self.traveler.current().span();
+
+        let t = if self.traveler.current().token_type == TokenType::Identifier {
+            Type::Union(Rc::new(self.traveler.current_content()))
+        } else {
+            let name = self.traveler.expect(TokenType::Type)?;
+
+            // there's one size of each number, the narrower names are reserved so they aren't taken to narrow the range
+            match (Type::from_name(&name), name.as_str()) {
+                (Some(t), _)  => t,
+                (None, "i32") => return Err(ParserError::new_pos(span, "unsupported type 'i32', integers are 64 bit 'i64'")),
+                (None, "f32") => return Err(ParserError::new_pos(span, "unsupported type 'f32', floats are 64 bit 'f64'")),
+                (None, _)     => return Err(ParserError::new_pos(span, &format!("unknown type '{}'", name))),
             }
-            
-            Ok(
-                Statement::Function(
-                    Function {
-                        name: left,
-                        params,
-                        body,
-                    }
-                )
-            )
+        };
+
+        self.traveler.next();
+
+        Ok(t)
+    }
+
+    fn binding(&mut self) -> ParserResult<Statement> {
+        let start = self.traveler.current().position;
+
+        self.traveler.next();
+
+        if self.traveler.current_content() == "rec" {
+            self.traveler.next();
+
+            return self.recursive(start)
+        }
+        
+        let left      = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+        let left_span = self.traveler.current().span();
+        self.traveler.next();
+        
+        if self.traveler.current().token_type == TokenType::Identifier || self.traveler.current_content() == "(" {
+            Ok(Statement::Function(self.function(left, start)?))
         } else {
             self.skip_whitespace()?;
+
+            let annotation = self.annotation()?;
+
             self.traveler.expect_content("=")?;
             self.traveler.next();
 
             let right = Rc::new(self.expression()?);
-            let left  = Rc::new(Expression::Identifier(left));
+            let left  = Rc::new(Expression::Identifier(left, left_span));
             
             Ok(
                 Statement::Binding(
                     Binding {
                         left,
+                        annotation,
                         right,
+                        span: self.traveler.span_from(start),
                     }
                 )
             )
         }
     }
 
-    fn assignment(&mut self, left: Rc<Expression>) -> ParserResult<Statement> {
+    // the rest of a function definition, after its name
+    fn function(&mut self, name: Rc<String>, start: TokenPosition) -> ParserResult<Function> {
+        let (params, param_types) = self.params(&["=", ":"])?;
+
+        let return_type = self.annotation()?;
+
+        self.traveler.expect_content("=")?;
+        self.traveler.next();
+        
+        let body = match self.traveler.current().token_type {
+            TokenType::EOL => Rc::new(self.block()?),
+            _              => Rc::new(self.expression()?)
+        };
+        
+        Ok(
+            Function {
+                name,
+                params,
+                param_types,
+                return_type,
+                body,
+                span: self.traveler.span_from(start),
+            }
+        )
+    }
+
+    // `let rec f x = ... and g y = ...`, functions that may call each other
+    fn recursive(&mut self, start: TokenPosition) -> ParserResult<Statement> {
+        let mut functions = Vec::new();
+
+        loop {
+            let function_start = self.traveler.current().position;
+
+            let name = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+            self.traveler.next();
+
+            if self.traveler.current().token_type != TokenType::Identifier && self.traveler.current_content() != "(" {
+                return Err(ParserError::new_pos(self.traveler.current().span(), &format!("{}: expected parameters of a recursive function", name)))
+            }
+
+            functions.push(self.function(name, function_start)?);
+
+            // look past line breaks for an `and`, stepping back if there isn't one
+            let mut skipped = 0;
+
+            while self.traveler.remaining() > 1 && (self.traveler.current_content() == "\n" || self.traveler.current().token_type == TokenType::EOL) {
+                self.traveler.next();
+                skipped += 1
+            }
+
+            if self.traveler.current().token_type == TokenType::Operator && self.traveler.current_content() == "and" {
+                self.traveler.next();
+            } else {
+                for _ in 0..skipped {
+                    self.traveler.prev();
+                }
+
+                break
+            }
+        }
+
+        Ok(
+            Statement::Recursive(
+                Recursive {
+                    functions,
+                    span: self.traveler.span_from(start),
+                }
+            )
+        )
+    }
+
+    // `type Shape = | Circle of f64 | Rect of f64 * f64`, each case may start its own line
+    fn type_declaration(&mut self) -> ParserResult<Statement> {
+        let start = self.traveler.current().position;
+
+        self.traveler.next();
+
+        let name = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+        self.traveler.next();
+
+        self.traveler.expect_content("=")?;
+        self.traveler.next();
+
+        self.skip_whitespace()?;
+
+        if self.traveler.current_content() == "|" {
+            self.traveler.next();
+        }
+
+        let mut constructors: Vec<(Rc<String>, Vec<Type>)> = Vec::new();
+
+        loop {
+            let position = self.traveler.current().span();
+
+            let tag = Rc::new(self.traveler.expect(TokenType::Identifier)?);
+
+            if !tag.starts_with(char::is_uppercase) {
+                return Err(ParserError::new_pos(position, &format!("{}: constructors start with an uppercase letter", tag)))
+            }
+
+            if constructors.iter().any(|(c, _)| *c == tag) {
+                return Err(ParserError::new_pos(position, &format!("{}: duplicate constructor", tag)))
+            }
+
+            self.traveler.next();
+
+            let mut fields = Vec::new();
+
+            if self.traveler.current().token_type == TokenType::Keyword && self.traveler.current_content() == "of" {
+                loop {
+                    self.traveler.next();
+                    fields.push(self.type_name()?);
+
+                    if self.traveler.current_content() != "*" {
+                        break
+                    }
+                }
+            }
+
+            constructors.push((tag, fields));
+
+            // look past line breaks for the next `|`, stepping back if there isn't one
+            let mut skipped = 0;
+
+            while self.traveler.remaining() > 1 && (self.traveler.current_content() == "\n" || self.traveler.current().token_type == TokenType::EOL || self.traveler.current().token_type == TokenType::Indent) {
+                self.traveler.next();
+                skipped += 1
+            }
+
+            if self.traveler.current_content() == "|" {
+                self.traveler.next();
+            } else {
+                for _ in 0..skipped {
+                    self.traveler.prev();
+                }
+
+                break
+            }
+        }
+
+        Ok(
+            Statement::TypeDecl(
+                TypeDecl {
+                    name,
+                    constructors,
+                    span: self.traveler.span_from(start),
+                }
+            )
+        )
+    }
+
+    fn assignment(&mut self, left: Rc<Expression>, start: TokenPosition) -> ParserResult<Statement> {
         self.traveler.next();
 
         let right = Rc::new(self.expression()?);
@@ -401,6 +976,7 @@ impl Parser {
                 Assignment {
                     left,
                     right,
+                    span: self.traveler.span_from(start),
                 }
             )
         )
@@ -418,11 +994,13 @@ impl Parser {
             },
             
             TokenType::Identifier => {
-                let a = Expression::Identifier(Rc::new(self.traveler.current_content().clone()));
+                let start = self.traveler.current().position;
+
+                let a = Expression::Identifier(Rc::new(self.traveler.current_content().clone()), self.traveler.current().span());
                 self.traveler.next();
                 
                 if self.traveler.current_content() == "=" {
-                    self.assignment(Rc::new(a))
+                    self.assignment(Rc::new(a), start)
                 } else {
                     self.traveler.prev();
                     Ok(Statement::Expression(Rc::new(self.expression()?)))
@@ -430,7 +1008,8 @@ impl Parser {
             },
             
             TokenType::Keyword => match self.traveler.current_content().as_str() {
-                "let" => Ok(self.binding()?),
+                "let"  => Ok(self.binding()?),
+                "type" => self.type_declaration(),
                 _     => Ok(Statement::Expression(Rc::new(self.expression()?))),
             },
             
@@ -438,8 +1017,8 @@ impl Parser {
         }
     }
     
-    fn operation(&mut self, expression: Expression) -> ParserResult<Expression> {
-        let mut ex_stack = vec![expression];
+    fn operation(&mut self, expression: Expression, span: Span, limit: u8) -> ParserResult<Expression> {
+        let mut ex_stack = vec![(expression, span)];
         let mut op_stack: Vec<(Operand, u8)> = Vec::new();
         
         op_stack.push(Operand::from_str(&self.traveler.current_content()).unwrap());
@@ -449,7 +1028,7 @@ impl Parser {
             self.traveler.next();
         }
         
-        let term = self.term()?;
+        let term = self.spanned_term()?;
 
         ex_stack.push(term);
         
@@ -457,7 +1036,7 @@ impl Parser {
         
         while ex_stack.len() > 1 {
             if !done {
-                if self.traveler.current().token_type != TokenType::Operator {
+                if !self.at_operator(limit) {
                     done = true;
                     continue
                 }
@@ -466,20 +1045,10 @@ impl Parser {
                 self.traveler.next();
 
                 if precedence >= op_stack.last().unwrap().1 {
-                    let left  = ex_stack.pop().unwrap();
-                    let right = ex_stack.pop().unwrap();
-
-                    ex_stack.push(
-                        Expression::Operation(
-                            Operation {
-                                right: Rc::new(left),
-                                op:    op_stack.pop().unwrap().0,
-                                left:  Rc::new(right)
-                            }
-                        )
-                    );
+                    let operation = Self::reduce(&mut ex_stack, op_stack.pop().unwrap().0);
+                    ex_stack.push(operation);
 
-                    let term = self.term()?;
+                    let term = self.spanned_term()?;
 
                     ex_stack.push(term);
                     op_stack.push((op, precedence));
@@ -487,26 +1056,60 @@ impl Parser {
                     continue
                 }
 
-                let term = self.term()?;
+                let term = self.spanned_term()?;
 
                 ex_stack.push(term);
                 op_stack.push((op, precedence));
             }
 
-            let left  = ex_stack.pop().unwrap();
-            let right = ex_stack.pop().unwrap();
-
-            ex_stack.push(
-                Expression::Operation(
-                    Operation {
-                        right: Rc::new(left),
-                        op:    op_stack.pop().unwrap().0,
-                        left:  Rc::new(right)
-                    }
-                )
-            );
+            let operation = Self::reduce(&mut ex_stack, op_stack.pop().unwrap().0);
+            ex_stack.push(operation);
         }
                 
-        Ok(ex_stack.pop().unwrap())
+        Ok(ex_stack.pop().unwrap().0)
+    }
+
+    // folds the top two operands into one operation spanning both
+    fn reduce(ex_stack: &mut Vec<(Expression, Span)>, op: Operand) -> (Expression, Span) {
+        let (left, left_span)   = ex_stack.pop().unwrap();
+        let (right, right_span) = ex_stack.pop().unwrap();
+
+        let span = Span::new(right_span.start, left_span.end);
+
+        match op {
+            Operand::Pipe     => return (Self::pipe(left, right, span), span),
+            Operand::PipeBack => return (Self::pipe(right, left, span), span),
+            _                 => (),
+        }
+
+        (
+            Expression::Operation(
+                Operation {
+                    right: Rc::new(left),
+                    op,
+                    left:  Rc::new(right),
+                    span,
+                }
+            ),
+            span
+        )
+    }
+
+    // `x |> f a` and `f a <| x` are the call `f a, x`
+    fn pipe(callee: Expression, arg: Expression, span: Span) -> Expression {
+        let (callee, mut args) = match callee {
+            Expression::Call(call) => (call.callee, call.args),
+            callee                 => (Rc::new(callee), Vec::new()),
+        };
+
+        args.push(Rc::new(arg));
+
+        Expression::Call(
+            Call {
+                callee,
+                args,
+                span,
+            }
+        )
     }
 }
@@ -1,4 +1,4 @@
-use super::{Token, TokenType};
+use super::{Token, TokenType, TokenPosition, Span};
 use super::{ParserError, ParserResult};
 
 #[derive(Debug, Clone)]
@@ -48,32 +48,67 @@ impl Traveler {
         &self.tokens[i]
     }
 
+    // end of the last consumed token, not counting line breaks and indentation
+    pub fn last_end(&self) -> TokenPosition {
+        let mut i = self.top.min(self.tokens.len());
+
+        while i > 1 {
+            match self.tokens[i - 1].token_type {
+                TokenType::EOL | TokenType::Indent => i -= 1,
+                _                                  => break,
+            }
+        }
+
+        match i {
+            0 => self.current().position,
+            _ => self.tokens[i - 1].end,
+        }
+    }
+
+    // how many indents start the line the current token is on
+    pub fn indentation(&self) -> usize {
+        let mut start = self.top.min(self.tokens.len());
+
+        while start > 0 && self.tokens[start - 1].token_type != TokenType::EOL {
+            start -= 1
+        }
+
+        self.tokens[start ..].iter().take_while(|t| t.token_type == TokenType::Indent).count()
+    }
+
+    pub fn span_from(&self, start: TokenPosition) -> Span {
+        Span::new(start, self.last_end())
+    }
+
+    // what delimits and operates, literals are read from the token so a string "," never passes for a comma
     pub fn current_content(&self) -> String {
-        self.current().content().clone()
+        match self.current().token_type {
+            TokenType::StringLiteral |
+            TokenType::CharLiteral   => String::new(),
+            _                        => self.current().content().clone(),
+        }
     }
 
     pub fn expect(&self, token: TokenType) -> ParserResult<String> {
         if self.current().token_type == token {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new_pos(self.current().position, &format!("expected '{:?}', found '{}'", token, self.current_content())))
+            Err(ParserError::new_pos(self.current().span(), &format!("expected '{:?}', found '{}'", token, self.current_content())))
         }
     }
 
     pub fn expect_content(&self, content: &str) -> ParserResult<String> {
-        if &self.current_content() == content {
+        if self.current_content() == content {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new_pos(self.current().position, &format!("expected '{}', found '{}'", content, self.current_content())))
+            Err(ParserError::new_pos(self.current().span(), &format!("expected '{}', found '{}'", content, self.current().content())))
         }
     }
 
     pub fn expect_contents(&self, sequence: Vec<String>) -> Result<Vec<&Token>, String> {
-        let mut accum: usize = 0;
-
         let mut res = Vec::new();
 
-        for c in sequence {
+        for (accum, c) in sequence.into_iter().enumerate() {
             if self.top + accum >= self.tokens.len() {
                 return Err(format!("expected '{}', found end of source >:(", c))
             }
@@ -83,8 +118,6 @@ impl Traveler {
             }
 
             res.push(self.get(self.top + accum));
-
-            accum += 1
         }
 
         Ok(res)
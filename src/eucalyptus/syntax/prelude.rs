@@ -0,0 +1,276 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::io::Write;
+
+use super::*;
+
+// where `print` and `println` write, shared so it can be redirected after the prelude is defined
+pub type Output = Rc<RefCell<Box<dyn Write>>>;
+
+fn function(params: Vec<Type>, ret: Type) -> Type {
+    Type::Function(params, Box::new(ret))
+}
+
+fn list(t: Type) -> Type {
+    Type::List(Box::new(t))
+}
+
+fn var(n: usize) -> Type {
+    Type::Var(n)
+}
+
+fn number(n: usize) -> Type {
+    Type::Constrained(n, Constraint::Number)
+}
+
+fn comparable(n: usize) -> Type {
+    Type::Constrained(n, Constraint::Comparable)
+}
+
+fn array(content: Vec<Value>) -> Value {
+    Value::Array(content.into_iter().map(Rc::new).collect())
+}
+
+fn invalid(name: &str, args: &[Value]) -> RunError {
+    RunError::new(&format!("{}: invalid arguments {:?}", name, args))
+}
+
+fn compare(a: &Value, b: &Value) -> RunResult<Ordering> {
+    let ordering = match (a, b) {
+        (Value::Int(a), Value::Int(b))     => Some(a.cmp(b)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Str(a), Value::Str(b))     => Some(a.cmp(b)),
+        (Value::Char(a), Value::Char(b))   => Some(a.cmp(b)),
+        _                                  => None,
+    };
+
+    ordering.ok_or_else(|| RunError::new(&format!("({:?}, {:?}): can't compare", a, b)))
+}
+
+fn print(out: &Output, name: &str, text: &str) -> RunResult<Value> {
+    let mut out = out.borrow_mut();
+
+    match out.write_all(text.as_bytes()).and_then(|_| out.flush()) {
+        Ok(_)  => Ok(Value::Nil),
+        Err(e) => Err(RunError::new(&format!("{}: {}", name, e))),
+    }
+}
+
+// the functions every script starts with, functions taking data take it last so it can be piped in
+//
+// the ones building strings or arrays charge them against the memory budget before building them
+fn prelude(out: &Output) -> Vec<Native> {
+    let print_out   = out.clone();
+    let println_out = out.clone();
+
+    vec![
+        Native::new("print", 1, Some(function(vec![var(0)], Type::Any)), move |args| {
+            print(&print_out, "print", &args[0].to_string())
+        }),
+
+        Native::new("println", 1, Some(function(vec![var(0)], Type::Any)), move |args| {
+            print(&println_out, "println", &format!("{}\n", args[0]))
+        }),
+
+        Native::with_context("to_string", 1, Some(function(vec![var(0)], Type::Str)), |args, context| {
+            let s = args[0].to_string();
+
+            context.allocate(s.len())?;
+            Ok(Value::Str(Rc::new(s)))
+        }),
+
+        Native::new("len", 1, Some(function(vec![list(var(0))], Type::Int)), |args| match args[0] {
+            Value::Array(ref a) => Ok(Value::Int(a.len() as i64)),
+            _                   => Err(invalid("len", args)),
+        }),
+
+        // in chars rather than bytes
+        Native::new("str_len", 1, Some(function(vec![Type::Str], Type::Int)), |args| match args[0] {
+            Value::Str(ref s) => Ok(Value::Int(s.chars().count() as i64)),
+            _                 => Err(invalid("str_len", args)),
+        }),
+
+        Native::with_context("concat", 2, Some(function(vec![Type::Str, Type::Str], Type::Str)), |args, context| match (&args[0], &args[1]) {
+            (Value::Str(a), Value::Str(b)) => {
+                context.allocate(a.len() + b.len())?;
+                Ok(Value::Str(Rc::new(format!("{}{}", a, b))))
+            },
+            _ => Err(invalid("concat", args)),
+        }),
+
+        // `split ", " s`
+        Native::with_context("split", 2, Some(function(vec![Type::Str, Type::Str], list(Type::Str))), |args, context| match (&args[0], &args[1]) {
+            (Value::Str(sep), _) if sep.is_empty() => Err(RunError::new("split: empty separator")),
+            (Value::Str(sep), Value::Str(s))       => {
+                context.allocate(array_size(s.matches(sep.as_str()).count() + 1) + s.len())?;
+                Ok(array(s.split(sep.as_str()).map(|p| Value::Str(Rc::new(p.to_owned()))).collect()))
+            },
+            _ => Err(invalid("split", args)),
+        }),
+
+        // `join ", " xs`
+        Native::with_context("join", 2, Some(function(vec![Type::Str, list(Type::Str)], Type::Str)), |args, context| match (&args[0], &args[1]) {
+            (Value::Str(sep), Value::Array(content)) => {
+                let mut parts = Vec::new();
+
+                for v in content {
+                    match **v {
+                        Value::Str(ref s) => parts.push(s.as_str()),
+                        _                 => return Err(invalid("join", args)),
+                    }
+                }
+
+                context.allocate(parts.iter().map(|p| p.len()).sum::<usize>() + sep.len() * parts.len().saturating_sub(1))?;
+                Ok(Value::Str(Rc::new(parts.join(sep))))
+            },
+            _ => Err(invalid("join", args)),
+        }),
+
+        Native::with_context("trim", 1, Some(function(vec![Type::Str], Type::Str)), |args, context| match args[0] {
+            Value::Str(ref s) => {
+                context.allocate(s.trim().len())?;
+                Ok(Value::Str(Rc::new(s.trim().to_owned())))
+            },
+            _ => Err(invalid("trim", args)),
+        }),
+
+        Native::with_context("chars", 1, Some(function(vec![Type::Str], list(Type::Char))), |args, context| match args[0] {
+            Value::Str(ref s) => {
+                context.allocate(array_size(s.chars().count()))?;
+                Ok(array(s.chars().map(Value::Char).collect()))
+            },
+            _ => Err(invalid("chars", args)),
+        }),
+
+        Native::new("abs", 1, Some(function(vec![number(0)], number(0))), |args| match args[0] {
+            Value::Int(n)   => n.checked_abs().map(Value::Int).ok_or_else(|| RunError::new(&format!("abs: {} overflows", n))),
+            Value::Float(n) => Ok(Value::Float(n.abs())),
+            _               => Err(invalid("abs", args)),
+        }),
+
+        Native::new("sqrt", 1, Some(function(vec![Type::Float], Type::Float)), |args| match args[0] {
+            Value::Float(n) => Ok(Value::Float(n.sqrt())),
+            _               => Err(invalid("sqrt", args)),
+        }),
+
+        Native::new("floor", 1, Some(function(vec![Type::Float], Type::Float)), |args| match args[0] {
+            Value::Float(n) => Ok(Value::Float(n.floor())),
+            _               => Err(invalid("floor", args)),
+        }),
+
+        Native::new("ceil", 1, Some(function(vec![Type::Float], Type::Float)), |args| match args[0] {
+            Value::Float(n) => Ok(Value::Float(n.ceil())),
+            _               => Err(invalid("ceil", args)),
+        }),
+
+        Native::new("min", 2, Some(function(vec![number(0), number(0)], number(0))), |args| {
+            match compare(&args[0], &args[1]).map_err(|e| RunError::new(&format!("min: {}", e)))? {
+                Ordering::Greater => Ok(args[1].clone()),
+                _                 => Ok(args[0].clone()),
+            }
+        }),
+
+        Native::new("max", 2, Some(function(vec![number(0), number(0)], number(0))), |args| {
+            match compare(&args[0], &args[1]).map_err(|e| RunError::new(&format!("max: {}", e)))? {
+                Ordering::Less => Ok(args[1].clone()),
+                _              => Ok(args[0].clone()),
+            }
+        }),
+
+        Native::with_context("map", 2, Some(function(vec![function(vec![var(0)], var(1)), list(var(0))], list(var(1)))), |args, context| match args[1] {
+            Value::Array(ref content) => {
+                context.allocate(array_size(content.len()))?;
+
+                let mut result = Vec::new();
+
+                for v in content {
+                    result.push(invoke(&args[0], vec![(**v).clone()], context)?)
+                }
+
+                Ok(array(result))
+            },
+            _ => Err(invalid("map", args)),
+        }),
+
+        // charged for everything it might keep
+        Native::with_context("filter", 2, Some(function(vec![function(vec![var(0)], Type::Bool), list(var(0))], list(var(0)))), |args, context| match args[1] {
+            Value::Array(ref content) => {
+                context.allocate(array_size(content.len()))?;
+
+                let mut result = Vec::new();
+
+                for v in content {
+                    match invoke(&args[0], vec![(**v).clone()], context)? {
+                        Value::Bool(true)  => result.push(v.clone()),
+                        Value::Bool(false) => (),
+                        c                  => return Err(RunError::new(&format!("filter: {:?}: non-bool condition", c))),
+                    }
+                }
+
+                Ok(Value::Array(result))
+            },
+            _ => Err(invalid("filter", args)),
+        }),
+
+        // `fold (fun acc x -> acc + x), 0, xs`
+        Native::with_context("fold", 3, Some(function(vec![function(vec![var(0), var(1)], var(0)), var(0), list(var(1))], var(0))), |args, context| match args[2] {
+            Value::Array(ref content) => {
+                let mut acc = args[1].clone();
+
+                for v in content {
+                    acc = invoke(&args[0], vec![acc, (**v).clone()], context)?
+                }
+
+                Ok(acc)
+            },
+            _ => Err(invalid("fold", args)),
+        }),
+
+        // from the first up to, but not including, the second
+        Native::with_context("range", 2, Some(function(vec![Type::Int, Type::Int], list(Type::Int))), |args, context| match (&args[0], &args[1]) {
+            (&Value::Int(a), &Value::Int(b)) => {
+                let len = usize::try_from((b as i128 - a as i128).max(0)).unwrap_or(usize::MAX);
+
+                context.allocate(array_size(len))?;
+                Ok(array((a .. b).map(Value::Int).collect()))
+            },
+            _ => Err(invalid("range", args)),
+        }),
+
+        Native::with_context("reverse", 1, Some(function(vec![list(var(0))], list(var(0)))), |args, context| match args[0] {
+            Value::Array(ref content) => {
+                context.allocate(array_size(content.len()))?;
+                Ok(Value::Array(content.iter().rev().cloned().collect()))
+            },
+            _ => Err(invalid("reverse", args)),
+        }),
+
+        Native::with_context("sort", 1, Some(function(vec![list(comparable(0))], list(comparable(0)))), |args, context| match args[0] {
+            Value::Array(ref content) => {
+                context.allocate(array_size(content.len()))?;
+
+                let mut sorted = content.clone();
+                let mut failed = None;
+
+                sorted.sort_by(|a, b| compare(a, b).unwrap_or_else(|e| {
+                    failed.get_or_insert(e);
+                    Ordering::Equal
+                }));
+
+                match failed {
+                    Some(e) => Err(RunError::new(&format!("sort: {}", e))),
+                    None    => Ok(Value::Array(sorted)),
+                }
+            },
+            _ => Err(invalid("sort", args)),
+        }),
+    ]
+}
+
+pub fn define_prelude(sym: &SymTab, env: &TypeTab, val: &ValTab, out: &Output) {
+    for native in prelude(out) {
+        define_native(sym, env, val, native)
+    }
+}
@@ -1,12 +1,35 @@
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 
 use std::fmt;
+use std::ptr;
 
+type Names = HashMap<String, usize>;
+
+// a name as it was before a `let` changed it, in the checker's view or evaluation's
+struct Change {
+    bound:    bool,
+    name:     String,
+    previous: Option<usize>,
+}
+
+// how far a scope had got, to roll back to
+pub struct SymMark {
+    size:  usize,
+    trail: usize,
+}
+
+// names to slots, each `let` taking a new slot so what was defined before it keeps the old one
+//
+// the checker declares a name before evaluation binds it, so each keeps its own view of the scope.
+// both are shared with snapshots until one of them changes, and every change is kept so it can be undone
 pub struct SymTab {
     parent: Option<Rc<SymTab>>,
-    names:  RefCell<HashMap<String, usize>>,
+    names:  RefCell<Rc<Names>>,
+    bound:  RefCell<Rc<Names>>,
+    size:   Cell<usize>,
+    trail:  RefCell<Vec<Change>>,
 }
 
 impl SymTab {
@@ -17,41 +40,130 @@ impl SymTab {
             hash_names.insert((**name).clone(), i);
         }
 
+        let hash_names = Rc::new(hash_names);
+
         SymTab {
             parent: Some(parent),
-            names:  RefCell::new(hash_names),
+            names:  RefCell::new(hash_names.clone()),
+            bound:  RefCell::new(hash_names),
+            size:   Cell::new(names.len()),
+            trail:  RefCell::new(Vec::new()),
         }
     }
 
     pub fn new_global() -> SymTab {
         SymTab {
             parent: None,
-            names:  RefCell::new(HashMap::new()),
+            names:  RefCell::new(Rc::new(HashMap::new())),
+            bound:  RefCell::new(Rc::new(HashMap::new())),
+            size:   Cell::new(0),
+            trail:  RefCell::new(Vec::new()),
         }
     }
 
+    // the slot of `name`, a new one if it isn't declared here, for globals defined from Rust
     pub fn add_name(&self, name: &str) -> usize {
-        if let Some(index) = self.names.borrow().get(name) {
+        if let Some(index) = self.bound.borrow().get(name) {
             return *index
         }
 
-        let new_index = self.names.borrow().len();
-        self.names.borrow_mut().insert(name.to_string(), new_index);
+        let index = self.declare(name);
+        self.insert(true, name, index);
+
+        index
+    }
+
+    // a new slot for `name`, shadowing any it had, as the checker sees a `let`
+    pub fn declare(&self, name: &str) -> usize {
+        let index = self.size.get();
+        self.size.set(index + 1);
+
+        self.insert(false, name, index);
+
+        index
+    }
+
+    // the slot evaluating a `let` fills, the one the checker last declared for it or else a new one
+    //
+    // scopes made for a call are only evaluated, while the global scope is checked and then evaluated
+    pub fn bind(&self, name: &str) -> usize {
+        let declared = self.names.borrow().get(name).cloned();
+
+        let index = match declared {
+            Some(index) if self.bound.borrow().get(name) != Some(&index) => index,
+            _                                                          => self.declare(name),
+        };
+
+        self.insert(true, name, index);
+
+        index
+    }
+
+    fn insert(&self, bound: bool, name: &str, index: usize) {
+        let names    = if bound { &self.bound } else { &self.names };
+        let previous = Rc::make_mut(&mut *names.borrow_mut()).insert(name.to_string(), index);
+
+        self.trail.borrow_mut().push(Change { bound, name: name.to_string(), previous })
+    }
+
+    // the bound names as they are now, so a closure keeps seeing what it was defined with
+    pub fn snapshot(&self) -> Rc<SymTab> {
+        let bound = self.bound.borrow().clone();
+
+        Rc::new(
+            SymTab {
+                parent: self.parent.as_ref().map(|p| p.snapshot()),
+                names:  RefCell::new(bound.clone()),
+                bound:  RefCell::new(bound),
+                size:   Cell::new(self.size.get()),
+                trail:  RefCell::new(Vec::new()),
+            }
+        )
+    }
+
+    pub fn mark(&self) -> SymMark {
+        SymMark {
+            size:  self.size.get(),
+            trail: self.trail.borrow().len(),
+        }
+    }
+
+    // undoes every declaration and binding since `mark`, latest first
+    pub fn rollback(&self, mark: &SymMark) {
+        let undone = self.trail.borrow_mut().split_off(mark.trail);
 
-        new_index
+        for change in undone.into_iter().rev() {
+            let mut names = if change.bound { self.bound.borrow_mut() } else { self.names.borrow_mut() };
+            let names     = Rc::make_mut(&mut *names);
+
+            match change.previous {
+                Some(index) => names.insert(change.name, index),
+                None        => names.remove(&change.name),
+            };
+        }
+
+        self.size.set(mark.size)
     }
 
+    // where the checker finds `name`, as (slot, scopes up)
     pub fn get_name(&self, name: &str) -> Option<(usize, usize)> {
-        self.get_name_internal(name, 0)
+        self.get_name_internal(name, 0, false)
+    }
+
+    // where evaluation finds `name`, skipping anything declared but not bound yet
+    pub fn get_bound(&self, name: &str) -> Option<(usize, usize)> {
+        self.get_name_internal(name, 0, true)
     }
 
-    fn get_name_internal(&self, name: &str, env_index: usize) -> Option<(usize, usize)> {
-        if let Some(index) = self.names.borrow().get(name) {
+    fn get_name_internal(&self, name: &str, env_index: usize, bound: bool) -> Option<(usize, usize)> {
+        let names = if bound { &self.bound } else { &self.names };
+
+        if let Some(index) = names.borrow().get(name) {
             return Some((*index, env_index));
         }
 
         match self.parent {
-            Some(ref parent) => parent.get_name_internal(name, env_index + 1),
+            Some(ref parent) => parent.get_name_internal(name, env_index + 1, bound),
             None => None,
         }
     }
@@ -72,22 +184,29 @@ impl SymTab {
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
-                try!(p.dump(f, env_index - 1));
-                try!(writeln!(f, "------------------------------"));
+                p.dump(f, env_index - 1)?;
+                writeln!(f, "------------------------------")?;
             }
         }
 
         for (i, v) in self.names.borrow().iter().enumerate() {
-            try!(writeln!(f, "({} : {}) = {:?}", i, env_index, v))
+            writeln!(f, "({} : {}) = {:?}", i, env_index, v)?
         }
 
         Ok(())
     }
 }
 
+// scopes are compared by identity
+impl PartialEq for SymTab {
+    fn eq(&self, other: &SymTab) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
 impl fmt::Debug for SymTab {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        try!(self.dump(f, 0));
+        self.dump(f, 0)?;
         Ok(())
     }
 }
@@ -1,5 +1,6 @@
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
 use super::*;
@@ -8,33 +9,357 @@ use super::*;
 pub enum Type {
     Str,
     Char,
-    Number,
+    Int,
+    Float,
     Bool,
     Any,
     Array(Vec<Rc<Type>>),
+    // an array of any length whose elements share one type, like those the prelude works on
+    List(Box<Type>),
+    // the result of a safe index, `Some value` or `None`
+    Option(Box<Type>),
+    Function(Vec<Type>, Box<Type>),
+    // fields by name, records of the same fields and field types are the same type
+    Record(BTreeMap<Rc<String>, Type>),
+    // a record with at least these fields, the variable standing for the rest, like a parameter's
+    Row(BTreeMap<Rc<String>, Type>, usize),
+    // a declared union, by name, see `Unions`
+    Union(Rc<String>),
+    // an unknown solved by unification, see `TypeTab::unify`
+    Var(usize),
+    // an unknown that can only be solved by the types meeting the constraint
+    Constrained(usize, Constraint),
+    // a let-bound type generalized over the listed variables
+    Forall(Vec<usize>, Box<Type>),
     Undefined,
 }
 
+// what a constrained variable can stand for, the types the prelude's arithmetic and ordering accept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    // Int or Float
+    Number,
+    // numbers, strings and chars
+    Comparable,
+}
+
+impl Constraint {
+    fn allows(self, t: &Type) -> bool {
+        match *t {
+            Type::Int | Type::Float => true,
+            Type::Str | Type::Char  => self == Constraint::Comparable,
+            _                       => false,
+        }
+    }
+
+    // what a variable under both has to meet, every number being comparable
+    fn and(self, other: Constraint) -> Constraint {
+        if self == Constraint::Number || other == Constraint::Number {
+            Constraint::Number
+        } else {
+            Constraint::Comparable
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Constraint::Number     => write!(f, "number"),
+            Constraint::Comparable => write!(f, "comparable"),
+        }
+    }
+}
+
+impl Type {
+    // the type named by an annotation keyword
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "i64"    => Some(Type::Int),
+            "f64"    => Some(Type::Float),
+            "char"   => Some(Type::Char),
+            "string" => Some(Type::Str),
+            "bool"   => Some(Type::Bool),
+            _        => None,
+        }
+    }
+
+    // type variables in order of first appearance, quantified ones excluded
+    pub fn vars(&self, found: &mut Vec<usize>) {
+        self.each_var(&mut |v, _| if !found.contains(&v) {
+            found.push(v)
+        })
+    }
+
+    // every appearance of a type variable along with its constraint, quantified ones excluded
+    fn each_var(&self, f: &mut dyn FnMut(usize, Option<Constraint>)) {
+        match *self {
+            Type::Var(v)            => f(v, None),
+            Type::Constrained(v, c) => f(v, Some(c)),
+
+            Type::Array(ref content) => for t in content {
+                t.each_var(f)
+            },
+
+            Type::Option(ref t) | Type::List(ref t) => t.each_var(f),
+
+            Type::Record(ref fields) => for t in fields.values() {
+                t.each_var(f)
+            },
+
+            Type::Row(ref fields, rest) => {
+                for t in fields.values() {
+                    t.each_var(f)
+                }
+                f(rest, None)
+            },
+
+            Type::Function(ref params, ref ret) => {
+                for t in params {
+                    t.each_var(f)
+                }
+                ret.each_var(f)
+            },
+
+            Type::Forall(ref bound, ref t) => t.each_var(&mut |v, c| if !bound.contains(&v) {
+                f(v, c)
+            }),
+
+            _ => (),
+        }
+    }
+
+    fn occurs(&self, var: usize) -> bool {
+        let mut found = Vec::new();
+        self.vars(&mut found);
+        found.contains(&var)
+    }
+
+    // replaces variables by the types in `map`, leaving the rest alone
+    fn substitute(&self, map: &HashMap<usize, Type>) -> Type {
+        match *self {
+            Type::Var(v) => match map.get(&v) {
+                Some(t) => t.clone(),
+                None    => self.clone(),
+            },
+
+            // renamed, a constraint stays on the new variable
+            Type::Constrained(v, c) => match map.get(&v) {
+                Some(&Type::Var(w)) => Type::Constrained(w, c),
+                Some(t)             => t.clone(),
+                None                => self.clone(),
+            },
+
+            Type::Array(ref content)            => Type::Array(content.iter().map(|t| Rc::new(t.substitute(map))).collect()),
+            Type::Option(ref t)                 => Type::Option(Box::new(t.substitute(map))),
+            Type::List(ref t)                   => Type::List(Box::new(t.substitute(map))),
+            Type::Function(ref params, ref ret) => Type::Function(params.iter().map(|t| t.substitute(map)).collect(), Box::new(ret.substitute(map))),
+            Type::Record(ref fields)            => Type::Record(fields.iter().map(|(n, t)| (n.clone(), t.substitute(map))).collect()),
+            Type::Forall(ref bound, ref t)      => Type::Forall(bound.clone(), Box::new(t.substitute(map))),
+
+            Type::Row(ref fields, rest) => {
+                let fields = fields.iter().map(|(n, t)| (n.clone(), t.substitute(map))).collect();
+
+                Type::with_rest(fields, map.get(&rest).cloned().unwrap_or(Type::Var(rest)))
+            },
+
+            _ => self.clone(),
+        }
+    }
+
+    // the fields of a row joined with what its rest stands for
+    fn with_rest(mut fields: BTreeMap<Rc<String>, Type>, rest: Type) -> Type {
+        match rest {
+            Type::Var(rest)          => Type::Row(fields, rest),
+            Type::Record(more)       => {
+                fields.extend(more);
+                Type::Record(fields)
+            },
+            Type::Row(more, rest)    => {
+                fields.extend(more);
+                Type::Row(fields, rest)
+            },
+            _                        => Type::Any,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, names: &[usize]) -> fmt::Result {
+        match *self {
+            Type::Array(ref content) => {
+                write!(f, "{{")?;
+                for (i, t) in content.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?
+                    }
+                    t.write(f, names)?
+                }
+                write!(f, "}}")
+            },
+
+            Type::Record(ref fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, t)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?
+                    }
+                    write!(f, "{}: ", name)?;
+                    t.write(f, names)?
+                }
+                write!(f, " }}")
+            },
+
+            Type::Row(ref fields, _) => {
+                write!(f, "{{ ")?;
+                for (name, t) in fields.iter() {
+                    write!(f, "{}: ", name)?;
+                    t.write(f, names)?;
+                    write!(f, "; ")?
+                }
+                write!(f, ".. }}")
+            },
+
+            Type::Option(ref t) => Type::write_postfix(f, t, "option", names),
+            Type::List(ref t)   => Type::write_postfix(f, t, "list", names),
+
+            Type::Function(ref params, ref ret) => {
+                if params.is_empty() {
+                    write!(f, "() -> ")?
+                }
+
+                for p in params {
+                    if let Type::Function(..) = *p {
+                        write!(f, "(")?;
+                        p.write(f, names)?;
+                        write!(f, ") -> ")?
+                    } else {
+                        p.write(f, names)?;
+                        write!(f, " -> ")?
+                    }
+                }
+
+                ret.write(f, names)
+            },
+
+            // 'a, 'b, .. in order of appearance, so equal types print the same
+            Type::Var(v) | Type::Constrained(v, _) => {
+                let i = names.iter().position(|n| *n == v).unwrap_or(0);
+
+                match i {
+                    0 ..= 25 => write!(f, "'{}", (b'a' + i as u8) as char),
+                    _        => write!(f, "'t{}", i),
+                }
+            },
+
+            Type::Forall(_, ref t) => t.write(f, names),
+
+            Type::Union(ref name) => write!(f, "{}", name),
+
+            ref t => write!(f, "{:?}", t),
+        }
+    }
+}
+
 impl Type {
-    pub fn compare(&self, other: &Type) -> bool {
-        if self == &Type::Any || other == &Type::Any {
-            true
+    // `Int option`, `(Int -> Int) list`
+    fn write_postfix(f: &mut fmt::Formatter, t: &Type, name: &str, names: &[usize]) -> fmt::Result {
+        if let Type::Function(..) = *t {
+            write!(f, "(")?;
+            t.write(f, names)?;
+            write!(f, ") {}", name)
         } else {
-            self == other
+            t.write(f, names)?;
+            write!(f, " {}", name)
         }
     }
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names = Vec::new();
+
+        let t = match *self {
+            Type::Forall(_, ref t) => t,
+            ref t                  => t,
+        };
+
+        t.vars(&mut names);
+
+        let mut constraints = Vec::new();
+
+        t.each_var(&mut |v, c| if let Some(c) = c {
+            if !constraints.contains(&(v, c)) {
+                constraints.push((v, c))
+            }
+        });
+
+        self.write(f, &names)?;
+
+        // `'a -> 'a when 'a: number`
+        for (i, &(v, c)) in constraints.iter().enumerate() {
+            write!(f, "{}", if i == 0 { " when " } else { ", " })?;
+            Type::Var(v).write(f, &names)?;
+            write!(f, ": {}", c)?
+        }
+
+        Ok(())
+    }
+}
+
+// solutions for the type variables created while inferring, shared by every frame of a TypeTab
+#[derive(Debug, Default)]
+pub struct Substitution {
+    bindings: Vec<Option<Type>>,
+    // the variables in the order they were solved, each is solved once
+    solved:   Vec<usize>,
+}
+
+// a case of a declared union, `Rect of f64 * f64` has two fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constructor {
+    pub union:  Rc<String>,
+    pub fields: Vec<Type>,
+}
+
+// the declared unions and their constructors, shared by every frame of a TypeTab like the substitution
+#[derive(Debug, Default)]
+pub struct Unions {
+    // constructor names in the order they're declared
+    unions:       HashMap<Rc<String>, Vec<Rc<String>>>,
+    constructors: HashMap<Rc<String>, Constructor>,
+    // what each declaration replaced, latest last
+    trail:        Vec<Declared>,
+}
+
+// a union and the constructors touched by declaring it, as they were before
+#[derive(Debug)]
+struct Declared {
+    name:         Rc<String>,
+    union:        Option<Vec<Rc<String>>>,
+    constructors: Vec<(Rc<String>, Option<Constructor>)>,
+}
+
+// how far the checker had got, to roll back to
+pub struct TypeMark {
+    types:    usize,
+    vars:     usize,
+    solved:   usize,
+    declared: usize,
+}
+
 pub struct TypeTab {
     parent: Option<Rc<TypeTab>>,
     types: RefCell<Vec<Type>>,
+    subst: Rc<RefCell<Substitution>>,
+    unions: Rc<RefCell<Unions>>,
 }
 
 impl TypeTab {
-    pub fn new(parent: Rc<TypeTab>, types: &Vec<Type>) -> TypeTab {
+    pub fn new(parent: Rc<TypeTab>, types: &[Type]) -> TypeTab {
         TypeTab {
+            subst: parent.subst.clone(),
+            unions: parent.unions.clone(),
             parent: Some(parent),
-            types: RefCell::new(types.clone()),
+            types: RefCell::new(types.to_vec()),
         }
     }
 
@@ -42,6 +367,8 @@ impl TypeTab {
         TypeTab {
             parent: None,
             types: RefCell::new(Vec::new()),
+            subst: Rc::new(RefCell::new(Substitution::default())),
+            unions: Rc::new(RefCell::new(Unions::default())),
         }
     }
 
@@ -52,6 +379,8 @@ impl TypeTab {
         }
 
         TypeTab {
+            subst: parent.subst.clone(),
+            unions: parent.unions.clone(),
             parent: Some(parent),
             types: RefCell::new(stack),
         }
@@ -98,25 +427,29 @@ impl TypeTab {
         }
 
         for (i, v) in self.types.borrow().iter().enumerate() {
-            println!("({} : {}) = {:?}", i, env_index, v)
+            println!("({} : {}) = {}", i, env_index, self.resolve(v))
         }
     }
 
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
-                try!(p.dump(f, env_index - 1));
-                try!(writeln!(f, "------------------------------"));
+                p.dump(f, env_index - 1)?;
+                writeln!(f, "------------------------------")?;
             }
         }
 
         for (i, v) in self.types.borrow().iter().enumerate() {
-            try!(writeln!(f, "({} : {}) = {:?}", i, env_index, v))
+            writeln!(f, "({} : {}) = {}", i, env_index, self.resolve(v))?
         }
 
         Ok(())
     }
 
+    pub fn parent(&self) -> Option<&Rc<TypeTab>> {
+        self.parent.as_ref()
+    }
+
     pub fn size(&self) -> usize {
         self.types.borrow().len()
     }
@@ -124,11 +457,301 @@ impl TypeTab {
     pub fn grow(&self) {
         self.types.borrow_mut().push(Type::Undefined)
     }
+
+    pub fn mark(&self) -> TypeMark {
+        let subst  = self.subst.borrow();
+        let unions = self.unions.borrow();
+
+        TypeMark {
+            types:    self.size(),
+            vars:     subst.bindings.len(),
+            solved:   subst.solved.len(),
+            declared: unions.trail.len(),
+        }
+    }
+
+    // forgets the slots of this frame, the variables and solutions, and the unions since `mark`
+    //
+    // slots are only ever set after they're made, so dropping the new ones is enough
+    pub fn rollback(&self, mark: &TypeMark) {
+        self.types.borrow_mut().truncate(mark.types);
+
+        let mut subst = self.subst.borrow_mut();
+
+        for v in subst.solved.split_off(mark.solved) {
+            subst.bindings[v] = None
+        }
+        subst.bindings.truncate(mark.vars);
+
+        let mut unions = self.unions.borrow_mut();
+
+        for declared in unions.trail.split_off(mark.declared).into_iter().rev() {
+            for (c, previous) in declared.constructors.into_iter().rev() {
+                match previous {
+                    Some(previous) => unions.constructors.insert(c, previous),
+                    None           => unions.constructors.remove(&c),
+                };
+            }
+
+            match declared.union {
+                Some(union) => unions.unions.insert(declared.name, union),
+                None        => unions.unions.remove(&declared.name),
+            };
+        }
+    }
+
+    // replaces any union of the same name, along with its constructors
+    pub fn declare_union(&self, name: &Rc<String>, constructors: &[(Rc<String>, Vec<Type>)]) {
+        let mut unions  = self.unions.borrow_mut();
+        let mut touched = Vec::new();
+
+        let union = unions.unions.remove(name);
+
+        for c in union.iter().flatten() {
+            touched.push((c.clone(), unions.constructors.remove(c)));
+        }
+
+        for (c, fields) in constructors {
+            let previous = unions.constructors.insert(c.clone(), Constructor { union: name.clone(), fields: fields.clone() });
+            touched.push((c.clone(), previous));
+        }
+
+        unions.unions.insert(name.clone(), constructors.iter().map(|(c, _)| c.clone()).collect());
+        unions.trail.push(Declared { name: name.clone(), union, constructors: touched });
+    }
+
+    pub fn union(&self, name: &Rc<String>) -> Option<Vec<Rc<String>>> {
+        self.unions.borrow().unions.get(name).cloned()
+    }
+
+    pub fn constructor(&self, name: &Rc<String>) -> Option<Constructor> {
+        self.unions.borrow().constructors.get(name).cloned()
+    }
+
+    pub fn fresh(&self) -> Type {
+        Type::Var(self.fresh_index())
+    }
+
+    // a new variable standing for one of the types `c` allows
+    pub fn fresh_constrained(&self, c: Constraint) -> Type {
+        Type::Constrained(self.fresh_index(), c)
+    }
+
+    fn fresh_index(&self) -> usize {
+        let mut subst = self.subst.borrow_mut();
+        subst.bindings.push(None);
+
+        subst.bindings.len() - 1
+    }
+
+    // a record with at least `fields`, whichever others it has are left to unification
+    pub fn row(&self, fields: BTreeMap<Rc<String>, Type>) -> Type {
+        Type::Row(fields, self.fresh_index())
+    }
+
+    // `t` with every solved variable replaced by its solution
+    pub fn resolve(&self, t: &Type) -> Type {
+        match *t {
+            Type::Var(v) | Type::Constrained(v, _) => {
+                let solved = self.subst.borrow().bindings.get(v).cloned().unwrap_or(None);
+
+                match solved {
+                    Some(solved) => self.resolve(&solved),
+                    None         => t.clone(),
+                }
+            },
+
+            Type::Array(ref content)            => Type::Array(content.iter().map(|t| Rc::new(self.resolve(t))).collect()),
+            Type::Option(ref t)                 => Type::Option(Box::new(self.resolve(t))),
+            Type::List(ref t)                   => Type::List(Box::new(self.resolve(t))),
+            Type::Function(ref params, ref ret) => Type::Function(params.iter().map(|t| self.resolve(t)).collect(), Box::new(self.resolve(ret))),
+            Type::Record(ref fields)            => Type::Record(fields.iter().map(|(n, t)| (n.clone(), self.resolve(t))).collect()),
+            Type::Forall(ref bound, ref t)      => Type::Forall(bound.clone(), Box::new(self.resolve(t))),
+            Type::Row(ref fields, rest)         => Type::with_rest(fields.iter().map(|(n, t)| (n.clone(), self.resolve(t))).collect(), self.resolve(&Type::Var(rest))),
+
+            _ => t.clone(),
+        }
+    }
+
+    pub fn unify(&self, a: &Type, b: &Type) -> RunResult<()> {
+        let a = self.resolve(a);
+        let b = self.resolve(b);
+
+        match (&a, &b) {
+            (Type::Any, _) | (_, Type::Any)             => Ok(()),
+            (Type::Undefined, _) | (_, Type::Undefined) => Ok(()),
+
+            (Type::Var(x), Type::Var(y)) if x == y => Ok(()),
+
+            (&Type::Var(v), t) | (t, &Type::Var(v)) => self.bind(v, t),
+
+            (&Type::Constrained(x, c), &Type::Constrained(y, d)) => if x == y {
+                Ok(())
+            } else {
+                let both = Type::Constrained(self.fresh_index(), c.and(d));
+
+                self.bind(x, &both)?;
+                self.bind(y, &both)
+            },
+
+            (&Type::Constrained(v, c), t) | (t, &Type::Constrained(v, c)) => if c.allows(t) {
+                self.bind(v, t)
+            } else {
+                Err(RunError::new(&format!("mismatched types: expected a {} type, found {}", c, t)))
+            },
+
+            (Type::Array(x), Type::Array(y)) if x.len() == y.len() => {
+                for (x, y) in x.iter().zip(y.iter()) {
+                    self.unify(x, y)?
+                }
+                Ok(())
+            },
+
+            (Type::Option(x), Type::Option(y)) | (Type::List(x), Type::List(y)) => self.unify(x, y),
+
+            // an array fits a list when each of its elements does
+            (Type::List(x), Type::Array(content)) | (Type::Array(content), Type::List(x)) => {
+                for t in content.iter() {
+                    self.unify(x, t)?
+                }
+                Ok(())
+            },
+
+            (Type::Record(x), Type::Record(y)) if x.keys().eq(y.keys()) => {
+                for (x, y) in x.values().zip(y.values()) {
+                    self.unify(x, y)?
+                }
+                Ok(())
+            },
+
+            // a row fits a record having its fields, its rest being the record's other fields
+            (Type::Row(x, rest), Type::Record(y)) | (Type::Record(y), Type::Row(x, rest)) if x.keys().all(|n| y.contains_key(n)) => {
+                for (n, t) in x.iter() {
+                    self.unify(t, &y[n])?
+                }
+
+                self.bind(*rest, &Type::Record(y.iter().filter(|(n, _)| !x.contains_key(*n)).map(|(n, t)| (n.clone(), t.clone())).collect()))
+            },
+
+            // two rows share their fields, each rest being the other's extra fields and a common rest
+            (Type::Row(x, x_rest), Type::Row(y, y_rest)) if x_rest != y_rest || x.keys().eq(y.keys()) => {
+                for (n, t) in x.iter() {
+                    if let Some(u) = y.get(n) {
+                        self.unify(t, u)?
+                    }
+                }
+
+                if x_rest == y_rest {
+                    return Ok(())
+                }
+
+                let only = |a: &BTreeMap<Rc<String>, Type>, b: &BTreeMap<Rc<String>, Type>| -> BTreeMap<Rc<String>, Type> {
+                    a.iter().filter(|(n, _)| !b.contains_key(*n)).map(|(n, t)| (n.clone(), t.clone())).collect()
+                };
+
+                let rest = self.fresh_index();
+
+                self.bind(*x_rest, &Type::Row(only(y, x), rest))?;
+                self.bind(*y_rest, &Type::Row(only(x, y), rest))
+            },
+
+            // functions are curried, `a -> b -> c` is also `a -> (b -> c)`
+            (Type::Function(x_params, x_ret), Type::Function(y_params, y_ret)) => {
+                for (x, y) in x_params.iter().zip(y_params.iter()) {
+                    self.unify(x, y)?
+                }
+
+                let n = x_params.len().min(y_params.len());
+
+                if x_params.len() > n {
+                    self.unify(&Type::Function(x_params[n ..].to_vec(), x_ret.clone()), y_ret)
+                } else if y_params.len() > n {
+                    self.unify(x_ret, &Type::Function(y_params[n ..].to_vec(), y_ret.clone()))
+                } else {
+                    self.unify(x_ret, y_ret)
+                }
+            },
+
+            (a, b) => if a == b {
+                Ok(())
+            } else {
+                Err(RunError::new(&format!("mismatched types: expected {}, found {}", a, b)))
+            },
+        }
+    }
+
+    fn bind(&self, v: usize, t: &Type) -> RunResult<()> {
+        if t.occurs(v) {
+            return Err(RunError::new(&format!("infinite type in {}", t)))
+        }
+
+        let mut subst = self.subst.borrow_mut();
+        subst.bindings[v] = Some(t.clone());
+        subst.solved.push(v);
+
+        Ok(())
+    }
+
+    // a copy of a let-bound type with fresh variables for the quantified ones
+    pub fn instantiate(&self, t: &Type) -> Type {
+        match *t {
+            Type::Forall(ref bound, ref t) => {
+                let map = bound.iter().map(|v| (*v, self.fresh())).collect();
+                t.substitute(&map)
+            },
+            _ => t.clone(),
+        }
+    }
+
+    // a type written outside of inference, like a native's, with its variables quantified
+    //
+    // they're renamed to fresh ones first, so they can't collide with variables already in use
+    pub fn declare(&self, t: &Type) -> Type {
+        let mut vars = Vec::new();
+        t.vars(&mut vars);
+
+        if vars.is_empty() {
+            return t.clone()
+        }
+
+        let map = vars.iter().map(|v| (*v, self.fresh())).collect();
+
+        self.generalize(&t.substitute(&map))
+    }
+
+    // quantifies the variables of `t` that no enclosing binding depends on
+    pub fn generalize(&self, t: &Type) -> Type {
+        let t = self.resolve(t);
+
+        let mut free = Vec::new();
+        t.vars(&mut free);
+
+        let mut fixed = Vec::new();
+        self.free_vars(&mut fixed);
+
+        let bound: Vec<usize> = free.into_iter().filter(|v| !fixed.contains(v)).collect();
+
+        if bound.is_empty() {
+            t
+        } else {
+            Type::Forall(bound, Box::new(t))
+        }
+    }
+
+    fn free_vars(&self, found: &mut Vec<usize>) {
+        for t in self.types.borrow().iter() {
+            self.resolve(t).vars(found)
+        }
+
+        if let Some(ref p) = self.parent {
+            p.free_vars(found)
+        }
+    }
 }
 
 impl fmt::Debug for TypeTab {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        try!(self.dump(f, 0));
+        self.dump(f, 0)?;
         Ok(())
     }
 }
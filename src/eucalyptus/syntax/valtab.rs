@@ -1,37 +1,169 @@
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt;
+use std::ptr;
+use std::collections::BTreeMap;
 
 use super::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum Value {
-    Number(f64),
+    Int(i64),
+    Float(f64),
     Bool(bool),
     Str(Rc<String>),
     Char(char),
     Array(Vec<Rc<Value>>),
-    Function(Vec<Rc<String>>, Vec<Statement>),
+    Option(Option<Rc<Value>>),
+    Record(BTreeMap<Rc<String>, Rc<Value>>),
+    // a union case by its constructor's name, with the constructor's arguments
+    Variant(Rc<String>, Vec<Rc<Value>>),
+    Function(Vec<Rc<String>>, Vec<Statement>, Rc<SymTab>, Rc<ValTab>),
+    Native(Native),
     Nil,
 }
 
+// captured scopes are left out, a function stored in the scope it closes over would print forever
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Int(ref n)                        => f.debug_tuple("Int").field(n).finish(),
+            Value::Float(ref n)                      => f.debug_tuple("Float").field(n).finish(),
+            Value::Bool(ref n)                       => f.debug_tuple("Bool").field(n).finish(),
+            Value::Str(ref n)                        => f.debug_tuple("Str").field(n).finish(),
+            Value::Char(ref n)                       => f.debug_tuple("Char").field(n).finish(),
+            Value::Array(ref n)                      => f.debug_tuple("Array").field(n).finish(),
+            Value::Option(Some(ref n))               => f.debug_tuple("Some").field(n).finish(),
+            Value::Option(None)                      => write!(f, "None"),
+            Value::Record(ref n)                     => f.debug_tuple("Record").field(n).finish(),
+            Value::Variant(ref tag, ref n)           => f.debug_tuple("Variant").field(tag).field(n).finish(),
+            Value::Function(ref params, ref body, ..) => f.debug_tuple("Function").field(params).field(body).finish(),
+            Value::Native(ref n)                     => n.fmt(f),
+            Value::Nil                               => write!(f, "Nil"),
+        }
+    }
+}
+
+// how values are printed by scripts, strings and chars without quotes
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Int(n)              => write!(f, "{}", n),
+            Value::Float(n)            => write!(f, "{:?}", n),
+            Value::Bool(n)             => write!(f, "{}", n),
+            Value::Str(ref n)          => write!(f, "{}", n),
+            Value::Char(n)             => write!(f, "{}", n),
+            Value::Array(ref content)  => {
+                write!(f, "{{")?;
+                for (i, v) in content.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?
+                    }
+                    write!(f, "{}", v)?
+                }
+                write!(f, "}}")
+            },
+            Value::Option(Some(ref v)) => write!(f, "Some {}", v),
+            Value::Option(None)        => write!(f, "None"),
+            Value::Record(ref fields)  => {
+                write!(f, "{{ ")?;
+                for (i, (name, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?
+                    }
+                    write!(f, "{} = {}", name, v)?
+                }
+                write!(f, " }}")
+            },
+            // `Red`, `Circle 2.0`, `Rect (1.0, 2.0)`
+            Value::Variant(ref tag, ref payload) => match payload.len() {
+                0 => write!(f, "{}", tag),
+                1 => match *payload[0] {
+                    Value::Variant(_, ref inner) if !inner.is_empty() => write!(f, "{} ({})", tag, payload[0]),
+                    ref v                                             => write!(f, "{} {}", tag, v),
+                },
+                _ => {
+                    write!(f, "{} (", tag)?;
+                    for (i, v) in payload.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?
+                        }
+                        write!(f, "{}", v)?
+                    }
+                    write!(f, ")")
+                },
+            },
+            Value::Function(..)        => write!(f, "<function>"),
+            Value::Native(ref n)       => write!(f, "<native {}>", n.name),
+            Value::Nil                 => write!(f, "nil"),
+        }
+    }
+}
+
+impl Value {
+    // the type a value from outside a script is checked as, functions can't be seen into
+    pub fn get_type(&self) -> Type {
+        match *self {
+            Value::Int(_)              => Type::Int,
+            Value::Float(_)            => Type::Float,
+            Value::Bool(_)             => Type::Bool,
+            Value::Str(_)              => Type::Str,
+            Value::Char(_)             => Type::Char,
+            Value::Array(ref c)        => Type::Array(c.iter().map(|v| Rc::new(v.get_type())).collect()),
+            Value::Option(Some(ref v)) => Type::Option(Box::new(v.get_type())),
+            Value::Option(None)        => Type::Option(Box::new(Type::Any)),
+            Value::Record(ref fields)  => Type::Record(fields.iter().map(|(n, v)| (n.clone(), v.get_type())).collect()),
+            Value::Variant(..)         => Type::Any,
+            Value::Native(ref n)       => n.get_type(),
+            Value::Function(..)        => Type::Any,
+            Value::Nil                 => Type::Any,
+        }
+    }
+
+    // what the value is, for error messages
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Value::Int(_)       => "Int",
+            Value::Float(_)     => "Float",
+            Value::Bool(_)      => "Bool",
+            Value::Str(_)       => "Str",
+            Value::Char(_)      => "Char",
+            Value::Array(_)     => "Array",
+            Value::Option(_)    => "Option",
+            Value::Record(_)    => "Record",
+            Value::Variant(..)  => "Variant",
+            Value::Function(..) => "Function",
+            Value::Native(_)    => "Function",
+            Value::Nil          => "Nil",
+        }
+    }
+}
+
 pub struct ValTab {
     parent: Option<Rc<ValTab>>,
     types: RefCell<Vec<Value>>,
+    // when off, operations on the wrong kind of value give Nil instead of an error
+    strict: Rc<Cell<bool>>,
+    // steps, depth and memory used by the running evaluation
+    context: Rc<Context>,
 }
 
 impl ValTab {
-    pub fn new(parent: Rc<ValTab>, types: &Vec<Value>) -> ValTab {
+    pub fn new(parent: Rc<ValTab>, types: &[Value]) -> ValTab {
         ValTab {
-            parent: Some(parent),
-            types:  RefCell::new(types.clone()),
+            strict:  parent.strict.clone(),
+            context: parent.context.clone(),
+            parent:  Some(parent),
+            types:   RefCell::new(types.to_vec()),
         }
     }
 
     pub fn new_global() -> ValTab {
         ValTab {
-            parent: None,
-            types:  RefCell::new(Vec::new()),
+            parent:  None,
+            types:   RefCell::new(Vec::new()),
+            strict:  Rc::new(Cell::new(true)),
+            context: Rc::new(Context::new(Limits::default())),
         }
     }
 
@@ -42,8 +174,40 @@ impl ValTab {
         }
 
         ValTab {
-            parent: Some(parent),
-            types: RefCell::new(stack),
+            strict:  parent.strict.clone(),
+            context: parent.context.clone(),
+            parent:  Some(parent),
+            types:   RefCell::new(stack),
+        }
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict.get()
+    }
+
+    pub fn set_strict(&self, strict: bool) {
+        self.strict.set(strict)
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    // the error in strict mode, Nil otherwise
+    pub fn or_nil(&self, error: RunError) -> RunResult<Value> {
+        if self.strict() {
+            Err(error)
+        } else {
+            Ok(Value::Nil)
+        }
+    }
+
+    // the error in strict mode, otherwise `Any` for what the checker rejects that gives Nil when run
+    pub fn or_any(&self, error: RunError) -> RunResult<Type> {
+        if self.strict() {
+            Err(error)
+        } else {
+            Ok(Type::Any)
         }
     }
 
@@ -95,18 +259,22 @@ impl ValTab {
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
-                try!(p.dump(f, env_index - 1));
-                try!(writeln!(f, "------------------------------"));
+                p.dump(f, env_index - 1)?;
+                writeln!(f, "------------------------------")?;
             }
         }
 
         for (i, v) in self.types.borrow().iter().enumerate() {
-            try!(writeln!(f, "({} : {}) = {:?}", i, env_index, v))
+            writeln!(f, "({} : {}) = {:?}", i, env_index, v)?
         }
 
         Ok(())
     }
 
+    pub fn parent(&self) -> Option<&Rc<ValTab>> {
+        self.parent.as_ref()
+    }
+
     pub fn size(&self) -> usize {
         self.types.borrow().len()
     }
@@ -114,11 +282,23 @@ impl ValTab {
     pub fn grow(&self) {
         self.types.borrow_mut().push(Value::Nil)
     }
+
+    // drops the slots past `size`
+    pub fn truncate(&self, size: usize) {
+        self.types.borrow_mut().truncate(size)
+    }
+}
+
+// scopes are compared by identity
+impl PartialEq for ValTab {
+    fn eq(&self, other: &ValTab) -> bool {
+        ptr::eq(self, other)
+    }
 }
 
 impl fmt::Debug for ValTab {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        try!(self.dump(f, 0));
+        self.dump(f, 0)?;
         Ok(())
     }
 }
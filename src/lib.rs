@@ -0,0 +1,7 @@
+#![allow(clippy::upper_case_acronyms, clippy::module_inception, clippy::should_implement_trait)]
+
+extern crate stacker;
+
+mod eucalyptus;
+
+pub use eucalyptus::*;
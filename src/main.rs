@@ -1,58 +1,125 @@
-use std::rc::Rc;
+extern crate eucalyptus;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, IsTerminal};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+mod repl;
 
-mod eucalyptus;
 use eucalyptus::*;
 
+const USAGE: &str = "usage: eucalyptus [--no-strict] [--max-steps=N] [--max-depth=N] [--timeout=MS] [--max-memory=BYTES] <file.euc | -e <expr> | - | repl>";
+
+// room for deeply nested source, which the parser and checker recurse through, calls grow their own stack
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// where the source came from, for diagnostics
+fn source_name(args: &[String]) -> &str {
+    match args.first().map(|a| a.as_str()) {
+        Some("-e")    => "<expr>",
+        Some("-")     => "<stdin>",
+        Some(path)    => path,
+        None          => "<unknown>",
+    }
+}
+
+fn read_source(args: &[String]) -> Result<String, String> {
+    match args.first().map(|a| a.as_str()) {
+        Some("-e") => match args.get(1) {
+            Some(expr) => Ok(expr.clone()),
+            None       => Err(format!("-e: missing expression\n{}", USAGE)),
+        },
+
+        Some("-") => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source).map_err(|e| format!("<stdin>: {}", e))?;
+            Ok(source)
+        },
+
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)),
+        None       => Err(USAGE.to_owned()),
+    }
+}
+
+// takes `--name=value` limits out of `args`
+fn limits(args: &mut Vec<String>) -> Result<Limits, String> {
+    let mut limits = Limits::default();
+    let mut rest   = Vec::new();
+
+    for arg in args.drain(..) {
+        let (flag, value) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (arg[.. i].to_owned(), arg[i + 1 ..].to_owned()),
+            _                                => {
+                rest.push(arg);
+                continue
+            },
+        };
+
+        let n = value.parse::<u64>().map_err(|_| format!("{}: expected a number, found '{}'", flag, value))?;
+
+        match flag.as_str() {
+            "--max-steps"  => limits.steps   = Some(n),
+            "--max-depth"  => limits.depth   = Some(n as usize),
+            "--timeout"    => limits.timeout = Some(Duration::from_millis(n)),
+            "--max-memory" => limits.memory  = Some(n as usize),
+            _              => return Err(format!("{}: unknown option\n{}", flag, USAGE)),
+        }
+    }
+
+    *args = rest;
+    Ok(limits)
+}
+
 fn main() {
-    let test = r#"
-let _ = 10
-_
-"#;
-
-    let lexer = lexer(&mut test.chars());
-
-    let traveler   = Traveler::new(lexer.collect());
-    let mut parser = Parser::new(traveler);
-
-    match parser.parse() {
-        Err(why)  => println!("error: {}", why),
-        Ok(stuff) => {
-            let symtab  = Rc::new(SymTab::new_global());
-            let typetab = Rc::new(TypeTab::new_global());
-
-            let valtab = Rc::new(ValTab::new_global());
-
-            let mut acc = 1;
-            
-            for s in stuff.iter() {
-                match s.visit(&symtab, &typetab, &valtab) {
-                    Ok(_)  => (),
-                    Err(e) => {
-                        println!("{}", e);
-                        break
-                    },
-                }
-
-                match s.get_type(&symtab, &typetab, &valtab) {
-                    Ok(_) => (),
-                    Err(e) => {
-                        println!("{}", e);
-                        break
-                    },
-                }
-                
-                match s.eval(&symtab, &valtab) {
-                    Ok(v) => if acc == stuff.len() {
-                        println!("{:#?}", v)
-                    },
-
-                    Err(e) => {
-                        println!("{}", e);
-                        break
-                    },
-                }
-                acc += 1
-            }
+    let interpreter = thread::Builder::new().stack_size(STACK_SIZE).spawn(start).unwrap();
+
+    if interpreter.join().is_err() {
+        process::exit(101)
+    }
+}
+
+fn start() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // lets code relying on operations quietly giving Nil keep running
+    let strict = !args.contains(&"--no-strict".to_owned());
+    args.retain(|a| a != "--no-strict");
+
+    let limits = match limits(&mut args) {
+        Ok(l)  => l,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2)
+        },
+    };
+
+    if args.first().map(|a| a.as_str()) == Some("repl") {
+        repl::repl(strict, limits);
+        return
+    }
+
+    let source = match read_source(&args) {
+        Ok(s)  => s,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2)
+        },
+    };
+
+    let interpreter = Interpreter::new();
+
+    interpreter.set_strict(strict);
+    interpreter.set_limits(limits);
+
+    match interpreter.eval_str(&source) {
+        Ok(Value::Nil) => (),
+        Ok(v)          => println!("{:#?}", v),
+        Err(e)              => {
+            eprint!("{}", report(source_name(&args), &source, &e, io::stderr().is_terminal()));
+            process::exit(1)
         },
     }
 }
@@ -0,0 +1,157 @@
+use std::io::{self, BufRead, Write, IsTerminal};
+
+use eucalyptus::*;
+
+
+const HELP: &str = ":type <expr>    show the type of an expression
+:ast <expr>     show the parsed statements
+:env            dump the global symbol, type and value tables
+:strict on|off  reject ill-typed code, or run it with wrong operations giving Nil
+:quit           leave the repl";
+
+struct Repl {
+    interpreter: Interpreter,
+}
+
+impl Repl {
+    fn eval(&self, source: &str) {
+        match self.interpreter.run(source) {
+            Ok((Value::Nil, _)) => (),
+            Ok((v, t))          => println!("{:?} : {}", v, t),
+            Err(e)              => print_diagnostics(source, &e),
+        }
+    }
+
+    // returns false when the repl should exit
+    fn command(&self, line: &str) -> bool {
+        let (command, rest) = match line.find(' ') {
+            Some(i) => (&line[.. i], line[i ..].trim()),
+            None    => (line, ""),
+        };
+
+        match command {
+            ":type" | ":t" => match self.interpreter.type_of(rest) {
+                Ok(t)  => println!("{}", t),
+                Err(e) => print_diagnostics(rest, &e),
+            },
+
+            ":ast" => match parse(rest) {
+                Ok(s)  => println!("{:#?}", s),
+                Err(e) => print_diagnostics(rest, &e),
+            },
+
+            ":env" => {
+                println!("symbols:");
+                self.interpreter.symtab().visualize(0);
+                println!("types:");
+                self.interpreter.typetab().visualize(0);
+                println!("values:");
+                self.interpreter.valtab().visualize(0)
+            },
+
+            ":strict" => match rest {
+                "on"  => self.interpreter.set_strict(true),
+                "off" => self.interpreter.set_strict(false),
+                ""    => println!("{}", if self.interpreter.valtab().strict() { "on" } else { "off" }),
+                _     => println!("usage: :strict on|off"),
+            },
+
+            ":help" | ":h"  => println!("{}", HELP),
+            ":quit" | ":q"  => return false,
+
+            c => println!("unknown command '{}', try :help", c),
+        }
+
+        true
+    }
+}
+
+fn print_diagnostics(source: &str, diagnostics: &[Diagnostic]) {
+    print!("{}", report("<repl>", source, diagnostics, io::stdout().is_terminal()))
+}
+
+// a line ending in '=' or '->' is the head of an indented block
+fn opens_block(line: &str) -> bool {
+    match lexer(&mut line.chars()).filter_map(Result::ok).last() {
+        Some(t) => t.token_type == TokenType::Symbol && (t.content() == "=" || t.content() == "->"),
+        None    => false,
+    }
+}
+
+fn starts_with(line: &str, keywords: &[&str]) -> bool {
+    let tokens = lexer(&mut line.trim_start().chars()).filter_map(Result::ok).take(keywords.len());
+
+    tokens.map(|t| t.content().clone()).collect::<Vec<_>>() == keywords
+}
+
+fn prompt(p: &str) {
+    print!("{}", p);
+    io::stdout().flush().unwrap()
+}
+
+pub fn repl(strict: bool, limits: Limits) {
+    let repl = Repl {
+        interpreter: Interpreter::new(),
+    };
+
+    repl.interpreter.set_strict(strict);
+    repl.interpreter.set_limits(limits);
+
+    let stdin = io::stdin();
+
+    let mut lines   = stdin.lock().lines();
+    let mut pending = None;
+
+    loop {
+        let line = match pending.take() {
+            Some(l) => l,
+            None    => {
+                prompt("> ");
+                match lines.next() {
+                    Some(Ok(l)) => l,
+                    _           => {
+                        println!();
+                        break
+                    },
+                }
+            },
+        };
+
+        if line.trim().is_empty() {
+            continue
+        }
+
+        if line.trim().starts_with(':') {
+            if repl.command(line.trim()) {
+                continue
+            }
+            break
+        }
+
+        let mut source = line.clone();
+
+        // a `let rec` group or a `type` goes on until a blank line, taking `and` and `|` lines as well as indented ones
+        if opens_block(&line) || starts_with(&line, &["let", "rec"]) || starts_with(&line, &["type"]) {
+            loop {
+                prompt(". ");
+                match lines.next() {
+                    Some(Ok(l)) => {
+                        if l.starts_with(' ') || l.starts_with('\t') || starts_with(&l, &["and"]) || starts_with(&l, &["|"]) {
+                            source.push('\n');
+                            source.push_str(&l)
+                        } else {
+                            if !l.trim().is_empty() {
+                                pending = Some(l)
+                            }
+                            break
+                        }
+                    },
+                    _ => break,
+                }
+            }
+        }
+
+        source.push('\n');
+        repl.eval(&source)
+    }
+}
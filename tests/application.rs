@@ -0,0 +1,113 @@
+extern crate eucalyptus;
+
+mod common;
+
+use eucalyptus::*;
+
+use common::{eval, fails, type_of};
+
+const ADD: &str = "let add a b = a + b\nlet add3 a b c = a + b + c\n";
+
+#[test]
+fn partial_application() {
+    assert_eq!(eval(ADD, "let inc = add 1\ninc 2"), "3");
+    assert_eq!(eval(ADD, "let f = add3 1\nlet g = f 2\ng 3"), "6");
+    assert_eq!(eval(ADD, "(add3 1, 2) 3"), "6");
+    assert_eq!(eval(ADD, "map (add 10), {1, 2}"), "{11, 12}");
+    assert_eq!(eval("", "map (concat \"a\"), {\"b\"}"), "{ab}");
+    assert_eq!(eval("", "let x = 1\nlet f a b = a + b + x\nlet g = f 1\nlet x = 100\ng 1"), "3");
+
+    assert_eq!(type_of(ADD, "add 1"), "Int -> Int");
+    assert_eq!(type_of(ADD, "add3 1, 2"), "Int -> Int");
+    assert_eq!(fails(ADD, "add \"a\""), "mismatched types: expected a number type, found Str");
+}
+
+#[test]
+fn over_application() {
+    let adder = "let adder x = fun y -> x + y\n";
+
+    assert_eq!(eval(adder, "adder 1, 2"), "3");
+    assert_eq!(eval(ADD, "let pick b = if b then add else fun x y -> x * y\n{(pick true, 2, 3), (pick false, 2, 3)}"), "{5, 6}");
+    assert_eq!(eval(ADD, "let curry f = fun a -> fun b -> f a, b\ncurry add, 1, 2"), "3");
+    assert_eq!(type_of(adder, "adder 1, 2"), "Int");
+
+    assert_eq!(fails(ADD, "add 1, 2, 3"), "mismatched types: expected Int, found Int -> 'a");
+    assert_eq!(fails("", "let id x = x\nid 1, 2"), "mismatched types: expected Int, found Int -> 'a");
+}
+
+#[test]
+fn from_rust() {
+    let interpreter = Interpreter::new();
+    interpreter.eval_str(ADD).unwrap();
+
+    let inc = interpreter.call_function("add", &[Value::Int(1)]).unwrap();
+    interpreter.define_global("inc", inc);
+
+    assert_eq!(interpreter.eval_str("inc 2").unwrap(), Value::Int(3));
+    assert_eq!(interpreter.call_function("add3", &[Value::Int(1), Value::Int(2), Value::Int(3)]).unwrap(), Value::Int(6));
+}
+
+fn natives() -> Interpreter {
+    let interpreter = Interpreter::new();
+    let int        = || Type::Int;
+
+    interpreter.define_native("plus", 2, Some(Type::Function(vec![int(), int()], Box::new(int()))), |args| match (&args[0], &args[1]) {
+        (&Value::Int(a), &Value::Int(b)) => Ok(Value::Int(a + b)),
+        _                                => Err(RunError::new("plus: expected integers")),
+    });
+
+    // gives a function, which over-application calls with the remaining arguments
+    interpreter.define_native("adder", 1, Some(Type::Function(vec![int()], Box::new(Type::Function(vec![int()], Box::new(int()))))), |args| {
+        let a = args[0].clone();
+
+        Ok(Value::Native(Native::new("added", 1, None, move |args| match (&a, &args[0]) {
+            (&Value::Int(a), &Value::Int(b)) => Ok(Value::Int(a + b)),
+            _                                => Err(RunError::new("added: expected integers")),
+        })))
+    });
+
+    interpreter.define_native("halve", 1, None, |args| match args[0] {
+        Value::Int(n) if n % 2 == 0 => Ok(Value::Int(n / 2)),
+        ref n                       => Err(RunError::new(&format!("halve: {} is odd", n))),
+    });
+
+    interpreter
+}
+
+#[test]
+fn calling_natives() {
+    let interpreter = natives();
+
+    assert_eq!(interpreter.eval_str("plus 1, 2").unwrap(), Value::Int(3));
+    assert_eq!(interpreter.eval_str("let inc = plus 1\ninc 2").unwrap(), Value::Int(3));
+    assert_eq!(interpreter.eval_str("map (plus 10), {1, 2}").unwrap().to_string(), "{11, 12}");
+    assert_eq!(interpreter.eval_str("adder 1, 2").unwrap(), Value::Int(3));
+    assert_eq!(interpreter.eval_str("(adder 1) 2").unwrap(), Value::Int(3));
+    assert_eq!(interpreter.eval_str("halve 4").unwrap(), Value::Int(2));
+
+    assert_eq!(interpreter.type_of("plus 1").unwrap().to_string(), "Int -> Int");
+    assert_eq!(interpreter.type_of("adder 1, 2").unwrap(), Type::Int);
+}
+
+#[test]
+fn native_errors() {
+    let interpreter = natives();
+
+    assert_eq!(interpreter.run("plus 1, \"a\"").unwrap_err()[0].message, "mismatched types: expected Int, found Str");
+    assert_eq!(interpreter.run("adder 'a'").unwrap_err()[0].message, "mismatched types: expected Int, found Char");
+    assert_eq!(interpreter.run("plus 1, 2, 3").unwrap_err()[0].message, "mismatched types: expected Int, found Int -> 'a");
+
+    // without a type, only the native itself can turn its arguments down
+    let errors = interpreter.run("let x = halve 4\nhalve 3").unwrap_err();
+
+    assert_eq!(errors[0].message, "halve: 3 is odd");
+    assert_eq!(report("<test>", "let x = halve 4\nhalve 3", &errors, false), "\
+error: halve: 3 is odd
+ --> <test>:2:1
+  |
+2 | halve 3
+  | ^^^^^^^
+");
+    assert_eq!(interpreter.run("x").unwrap_err()[0].message, "x: undeclared use");
+    assert_eq!(interpreter.run("halve \"a\"").unwrap_err()[0].message, "halve: a is odd");
+}
@@ -0,0 +1,92 @@
+extern crate eucalyptus;
+
+mod common;
+
+use eucalyptus::*;
+
+use common::{eval, fails, type_of};
+
+#[test]
+fn shadowing() {
+    assert_eq!(eval("", "let x = 1\nlet x = x + 1\nx"), "2");
+    assert_eq!(eval("", "let x = 1\nlet x = \"a\"\nx"), "a");
+    assert_eq!(type_of("", "let x = 1\nlet x = 'a'\nx"), "Char");
+}
+
+#[test]
+fn blocks_keep_their_bindings() {
+    let branch = "let x = 1\nlet y = if false then\n  let z = 1\n  z\nelse 0\n";
+
+    assert_eq!(fails(branch, "z"), "z: undeclared use");
+    assert_eq!(eval(branch, "x + y"), "1");
+
+    let shadowed = "let x = 1\nlet y = if true then\n  let x = \"a\"\n  x\nelse \"b\"\n";
+
+    assert_eq!(eval(shadowed, "{x, (x + 1)}"), "{1, 2}");
+    assert_eq!(type_of(shadowed, "x"), "Int");
+    assert_eq!(fails("", "match 1 with\n| _ ->\n  let w = 2\n  w\nw"), "w: undeclared use");
+}
+
+#[test]
+fn closures_keep_what_they_captured() {
+    assert_eq!(eval("", "let x = 1\nlet f y = x + y\nlet x = 10\nf 0"), "1");
+    assert_eq!(eval("", "let x = 1\nlet f = fun y -> x + y\nlet x = \"s\"\nf 0"), "1");
+    assert_eq!(eval("", "let x = 1\nlet f y = x + y\nlet x = \"s\"\nlet g = f\ng 2"), "3");
+    assert_eq!(fails("", "let x = 1\nlet f y = x + y\nlet x = \"s\"\nf x"), "mismatched types: expected Int, found Str");
+}
+
+#[test]
+fn recursion_sees_its_own_definition() {
+    let source = "
+let f n = if n == 0 then 0 else 1 + f (n - 1)
+let g = f
+let f n = 100
+g 3
+";
+
+    assert_eq!(eval("", source), "3");
+    assert_eq!(eval("", "let rec even n = if n == 0 then true else odd (n - 1)\nand odd n = if n == 0 then false else even (n - 1)\nlet odd n = true\neven 3"), "false");
+}
+
+#[test]
+fn failed_inputs_define_nothing() {
+    let interpreter = Interpreter::new();
+
+    assert!(interpreter.run("let x = 1 + \"a\"").is_err());
+    assert_eq!(interpreter.run("x + 1").unwrap_err()[0].message, "x: undeclared use");
+
+    assert!(interpreter.run("let y = 1 / 0").is_err());
+    assert_eq!(interpreter.run("y + 1").unwrap_err()[0].message, "y: undeclared use");
+
+    interpreter.run("let z = 1").unwrap();
+    assert!(interpreter.run("let z = \"a\"\nlet w = z / 0").is_err());
+    assert_eq!(interpreter.eval_str("z + 1").unwrap(), Value::Int(2));
+
+    assert!(interpreter.run("let f n = n + \"a\"").is_err());
+    assert_eq!(interpreter.run("f 1").unwrap_err()[0].message, "f: undeclared use");
+
+    interpreter.run("type Shape = | Circle of f64 | Dot").unwrap();
+    assert!(interpreter.run("type Shape = | Square of i64\nlet s = Square \"a\"").is_err());
+    assert_eq!(interpreter.eval_str("Circle 1.0").unwrap().to_string(), "Circle 1.0");
+    assert_eq!(interpreter.run("Square 1").unwrap_err()[0].message, "Square: undeclared use");
+
+    interpreter.run("let id x = x").unwrap();
+    assert!(interpreter.run("let a = id 1\nlet b = a + \"s\"").is_err());
+    assert_eq!(interpreter.type_of("id").unwrap().to_string(), "'a -> 'a");
+}
+
+#[test]
+fn checking_defines_nothing() {
+    let interpreter = Interpreter::new();
+
+    assert_eq!(interpreter.type_of("let x = 1\nx").unwrap(), Type::Int);
+    assert_eq!(interpreter.run("x").unwrap_err()[0].message, "x: undeclared use");
+
+    interpreter.run("let y = 'a'").unwrap();
+    assert_eq!(interpreter.type_of("let y = 1\ny").unwrap(), Type::Int);
+    assert_eq!(interpreter.type_of("y").unwrap(), Type::Char);
+    assert_eq!(interpreter.eval_str("y").unwrap(), Value::Char('a'));
+
+    assert!(interpreter.type_of("let z = 1 + \"a\"").is_err());
+    assert_eq!(interpreter.run("z").unwrap_err()[0].message, "z: undeclared use");
+}
@@ -0,0 +1,52 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+#[test]
+fn line_comments() {
+    assert_eq!(eval("", "1 + 2 -- three"), "3");
+    assert_eq!(eval("-- a script\nlet x = 1 -- one\n", "x + 1 --"), "2");
+    assert_eq!(type_of("let s = \"a\" -- a Str\n", "s"), "Str");
+
+    // a comment isn't code, a name in one is still undeclared
+    assert_eq!(fails("-- let y = 1\n", "y"), "y: undeclared use");
+}
+
+#[test]
+fn comment_lines_inside_blocks() {
+    let source = "
+let f n = -- doubles, plus one
+  -- a comment line inside the block
+  let m = n * 2 -- keeps the block going
+      -- indented past the block
+-- or before it
+  (* a block comment on its own line *)
+
+  m + 1
+";
+
+    assert_eq!(eval(source, "f 1"), "3");
+    assert_eq!(type_of(source, "f"), "Int -> Int");
+
+    let branches = "
+let sign x =
+  if x > 0 then
+    -- positive
+    1
+  else
+    -- zero or less
+    0
+";
+
+    assert_eq!(eval(branches, "{(sign 5), (sign 0)}"), "{1, 0}");
+}
+
+#[test]
+fn comment_markers_in_strings() {
+    assert_eq!(eval("", "\"a -- b\""), "a -- b");
+    assert_eq!(eval("", "\"(* not a comment *)\""), "(* not a comment *)");
+    assert_eq!(eval("", "len (chars \"--\") -- two"), "2");
+    assert_eq!(eval("let s = \"x -- y\" (* z *)\n", "s"), "x -- y");
+}
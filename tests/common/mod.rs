@@ -0,0 +1,28 @@
+use eucalyptus::*;
+
+// each runs `prefix` then `source` in a fresh interpreter, the prefix holding what the snippets share
+
+pub fn eval(prefix: &str, source: &str) -> String {
+    let source = format!("{}{}", prefix, source);
+
+    match Interpreter::new().eval_str(&source) {
+        Ok(v)  => v.to_string(),
+        Err(e) => panic!("{}", report("<test>", &source, &e, false)),
+    }
+}
+
+pub fn type_of(prefix: &str, source: &str) -> String {
+    let source = format!("{}{}", prefix, source);
+
+    match Interpreter::new().type_of(&source) {
+        Ok(t)  => t.to_string(),
+        Err(e) => panic!("{}", report("<test>", &source, &e, false)),
+    }
+}
+
+pub fn fails(prefix: &str, source: &str) -> String {
+    match Interpreter::new().run(&format!("{}{}", prefix, source)) {
+        Ok((v, _)) => panic!("expected an error, found {}", v),
+        Err(e)     => e[0].message.clone(),
+    }
+}
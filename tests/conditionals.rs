@@ -0,0 +1,92 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+const SIGN: &str = "let sign x = if x > 0 then 1 elif x < 0 then -1 else 0\n";
+
+#[test]
+fn inline() {
+    assert_eq!(eval("", "if true then 1 else 2"), "1");
+    assert_eq!(eval("", "if 1 > 2 then \"a\" else \"b\""), "b");
+    assert_eq!(eval(SIGN, "{(sign 5), (sign (0 - 5)), (sign 0)}"), "{1, -1, 0}");
+    assert_eq!(eval("", "if false then 1 elif false then 2 elif true then 3 else 4"), "3");
+    assert_eq!(type_of(SIGN, "sign"), "Int -> Int");
+
+    assert_eq!(fails("", "if 1 then 2 else 3"), "Int: non-bool condition");
+    assert_eq!(fails("", "if true then 1 else \"a\""), "(Int, Str): mismatched if branches");
+    assert_eq!(fails("", "if true then 1"), "expected 'else', found end of source");
+}
+
+#[test]
+fn blocks() {
+    let describe = "
+let describe x =
+  if x > 10 then
+    let big = \"big\"
+    big
+  elif x > 5 then
+    \"medium\"
+  else
+    \"small\"
+";
+
+    assert_eq!(eval(describe, "{(describe 20), (describe 7), (describe 1)}"), "{big, medium, small}");
+
+    let bound = "
+let x = 5
+let r = if x > 0 then
+    x * 2
+  else
+    0
+r
+";
+
+    assert_eq!(eval("", bound), "10");
+}
+
+#[test]
+fn nested_blocks() {
+    let inner = "
+let f n =
+  let g m =
+    if m > 0 then
+      \"pos\"
+    else
+      \"neg\"
+  g n
+";
+
+    assert_eq!(eval(inner, "{(f 1), (f (0 - 1))}"), "{pos, neg}");
+
+    let nested = "
+let classify x =
+  if x > 0 then
+    if x > 3 then
+      \"big\"
+    else
+      \"small\"
+  else
+    if x == 0 then
+      \"zero\"
+    else
+      \"neg\"
+";
+
+    assert_eq!(eval(nested, "{(classify 5), (classify 2), (classify 0), (classify (0 - 2))}"), "{big, small, zero, neg}");
+
+    let deeper = "
+let x = 5
+let r = if x > 0 then
+    if x > 3 then
+      \"big\"
+    else
+      \"small\"
+  else
+    \"neg\"
+r
+";
+
+    assert_eq!(eval("", deeper), "big");
+}
@@ -0,0 +1,88 @@
+extern crate eucalyptus;
+
+use eucalyptus::*;
+
+#[test]
+fn errors_point_at_the_source() {
+    let source = "let x = 1\nlet y = x + \"a\"";
+    let errors = Interpreter::new().run(source).unwrap_err();
+
+    assert_eq!(report("file.euc", source, &errors, false), "\
+error: (Int+Str): failed to operate
+ --> file.euc:2:9
+  |
+2 | let y = x + \"a\"
+  |         ^^^^^^^
+");
+}
+
+#[test]
+fn notes_and_help() {
+    let span = Span::new(TokenPosition::new(1, 4, 4), TokenPosition::new(1, 5, 5));
+
+    let warning = Diagnostic::new(Severity::Warning, "unused binding", Some(span))
+        .with_note("`x` is never read")
+        .with_help("name it `_` to ignore it");
+
+    assert_eq!(warning.render("<test>", "let x = 1", false), "\
+warning: unused binding
+ --> <test>:1:5
+  |
+1 | let x = 1
+  |     ^
+  = note: `x` is never read
+  = help: name it `_` to ignore it
+");
+
+    assert_eq!(Diagnostic::error("no source", None).with_note("a").render("<test>", "", false), "\
+error: no source
+ --> <test>
+  = note: a
+");
+}
+
+#[test]
+fn lexer_and_parser_errors_underline_their_tokens() {
+    let render = |source: &str| report("<test>", source, &Interpreter::new().run(source).unwrap_err(), false);
+
+    assert_eq!(render("let s = \"a\\qb\""), "\
+error: invalid character escape: \\q
+ --> <test>:1:9
+  |
+1 | let s = \"a\\qb\"
+  |         ^^^^^^
+");
+
+    assert_eq!(render("let 42 = x"), "\
+error: expected 'Identifier', found '42'
+ --> <test>:1:5
+  |
+1 | let 42 = x
+  |     ^^
+");
+}
+
+#[test]
+fn errors_explain_themselves() {
+    let source = "let add a b = a + b\nadd 1, \"a\"";
+
+    assert_eq!(report("<test>", source, &Interpreter::new().run(source).unwrap_err(), false), "\
+error: mismatched types: expected Int, found Str
+ --> <test>:2:1
+  |
+2 | add 1, \"a\"
+  | ^^^^^^^^^^
+  = note: `add` has type 'a -> 'a -> 'a when 'a: number
+");
+
+    let source = "let y = if true then\n  let z = 1\n  z\nelse 0\nz";
+
+    assert_eq!(report("<test>", source, &Interpreter::new().run(source).unwrap_err(), false), "\
+error: z: undeclared use
+ --> <test>:5:1
+  |
+5 | z
+  | ^
+  = help: a name is seen after its `let`, and only inside the block defining it
+");
+}
@@ -0,0 +1,22 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+#[test]
+fn literal_arrays() {
+    assert_eq!(eval("", "{1, 2, 3}[0 - 1]"), "3");
+    assert_eq!(eval("", "{1, 2, 3}[5]?"), "None");
+    assert_eq!(fails("", "{1, 2, 3}[5]"), "index 5 out of bounds for length 3");
+}
+
+#[test]
+fn parameters_are_indexed_as_lists() {
+    assert_eq!(type_of("let first xs = xs[0]\n", "first"), "'a list -> 'a");
+    assert_eq!(type_of("let first xs = xs[0]\n", "first {1}"), "Int");
+    assert_eq!(eval("let f xs = xs[0] + 1\n", "f {2}"), "3");
+
+    assert_eq!(fails("let first xs = xs[0]\n", "let s: string = first {1}"), "mismatched types: expected Str, found Int");
+    assert_eq!(fails("let f xs = xs[0] + 1\n", "f {\"a\"}"), "mismatched types: expected Int, found Str");
+}
@@ -0,0 +1,52 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+#[test]
+fn let_polymorphism() {
+    let id = "let id x = x\n";
+
+    assert_eq!(type_of(id, "id"), "'a -> 'a");
+    assert_eq!(type_of(id, "{(id 1), (id \"a\")}"), "{Int, Str}");
+    assert_eq!(eval(id, "{(id 1), (id \"a\")}"), "{1, a}");
+
+    // a parameter isn't generalized, only what `let` binds
+    assert!(fails("", "let f g = {(g 1), (g \"a\")}").starts_with("mismatched types"));
+}
+
+#[test]
+fn higher_order() {
+    let twice = "let twice f x = f (f x)\n";
+
+    assert_eq!(type_of(twice, "twice"), "('a -> 'a) -> 'a -> 'a");
+    assert_eq!(type_of(twice, "twice (fun x -> x + 1)"), "Int -> Int");
+    assert_eq!(eval(twice, "twice (fun x -> x + 1), 5"), "7");
+    assert_eq!(eval(twice, "twice (fun s -> concat s, \"!\"), \"hi\""), "hi!!");
+    assert_eq!(fails(twice, "twice (fun x -> x > 1), 5"), "mismatched types: expected Int, found Bool");
+
+    assert_eq!(type_of("", "let compose f g x = f (g x)\ncompose"), "('a -> 'b) -> ('c -> 'a) -> 'c -> 'b");
+}
+
+#[test]
+fn occurs_check() {
+    assert_eq!(fails("", "let f x = x x"), "infinite type in 'a -> 'b");
+    assert_eq!(fails("", "let f xs = map f, xs"), "infinite type in 'a list");
+}
+
+#[test]
+fn annotations() {
+    assert_eq!(type_of("", "let add (a: f64) b = a + b\nadd"), "Float -> Float -> Float");
+    assert_eq!(type_of("", "let name (s: string) = s\nname"), "Str -> Str");
+
+    // numbers only come in 64 bits
+    assert_eq!(fails("", "let x: i32 = 10"), "unsupported type 'i32', integers are 64 bit 'i64'");
+    assert_eq!(fails("", "let half (x: f32) = x / 2.0"), "unsupported type 'f32', floats are 64 bit 'f64'");
+    assert_eq!(fails("", "type T = | A of i32"), "unsupported type 'i32', integers are 64 bit 'i64'");
+
+    assert_eq!(fails("", "let x: string = 1"), "mismatched types: expected Str, found Int");
+    assert_eq!(fails("", "let g (x: i64): string = x"), "mismatched types: expected Str, found Int");
+    assert_eq!(fails("", "let add (a: f64) b = a + b\nadd 1.0, 2"), "mismatched types: expected Float, found Int");
+    assert_eq!(fails("", "let f = fun (b: bool) -> b\nf 1"), "mismatched types: expected Bool, found Int");
+}
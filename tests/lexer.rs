@@ -0,0 +1,46 @@
+extern crate eucalyptus;
+
+use eucalyptus::*;
+
+// each error the lexer found in `source`, as (message, line, column, width)
+fn errors(source: &str) -> Vec<(String, usize, usize, usize)> {
+    lexer(&mut source.chars()).tokens().unwrap_err().iter().map(|e| {
+        (e.message().to_owned(), e.span.start.line, e.span.start.col, e.span.end.col - e.span.start.col)
+    }).collect()
+}
+
+fn error(message: &str, line: usize, col: usize, width: usize) -> (String, usize, usize, usize) {
+    (message.to_owned(), line, col, width)
+}
+
+#[test]
+fn literals() {
+    assert_eq!(errors("let s = \"abc"), vec![error("unterminated literal", 1, 8, 4)]);
+    assert_eq!(errors("let s = \"a\\qb\""), vec![error("invalid character escape: \\q", 1, 8, 6)]);
+    assert_eq!(errors("let c = 'ab'"), vec![error("invalid char literal: 'ab'", 1, 8, 4)]);
+    assert_eq!(errors("let x = 1.2.3"), vec![error("illegal decimal point in '1.2.3'", 1, 8, 5)]);
+    assert_eq!(errors("(* never closed"), vec![error("unterminated block comment", 1, 0, 15)]);
+}
+
+#[test]
+fn stray_characters() {
+    assert_eq!(errors("let x = 1 $ 2"), vec![error("invalid token '$'", 1, 10, 1)]);
+    assert_eq!(errors("~"), vec![error("invalid token '~'", 1, 0, 1)]);
+}
+
+#[test]
+fn every_error_in_one_pass() {
+    let source = "let a = $\nlet b = \"x\\q\"\nlet c = 1 ~ 2\nlet d = \"open";
+
+    assert_eq!(errors(source), vec![
+        error("invalid token '$'", 1, 8, 1),
+        error("invalid character escape: \\q", 2, 8, 5),
+        error("invalid token '~'", 3, 10, 1),
+        error("unterminated literal", 4, 8, 5),
+    ]);
+
+    let diagnostics = Interpreter::new().run(source).unwrap_err();
+
+    assert_eq!(diagnostics.len(), 4);
+    assert_eq!(diagnostics[3].message, "unterminated literal");
+}
@@ -0,0 +1,112 @@
+extern crate eucalyptus;
+
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eucalyptus::*;
+
+// on a thread with the default stack, like an embedder's, not the binary's large one
+fn run_on_default_thread(source: &'static str, limits: Limits) -> Result<String, String> {
+    let run = thread::spawn(move || {
+        let interpreter = Interpreter::new();
+
+        interpreter.set_limits(limits);
+
+        interpreter.eval_str(source).map(|v| v.to_string()).map_err(|e| e[0].message.clone())
+    });
+
+    run.join().unwrap()
+}
+
+fn fails_on_default_thread(source: &'static str, limits: Limits) -> String {
+    match run_on_default_thread(source, limits) {
+        Ok(v)  => panic!("expected an error, found {}", v),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn deep_recursion_hits_the_depth_limit() {
+    let source = "let rec d n = if n == 0 then 0 else 1 + (d (n - 1))\nd 100000";
+
+    assert_eq!(fails_on_default_thread(source, Limits::default()), "call depth limit of 5000 exceeded");
+}
+
+#[test]
+fn recursion_deeper_than_the_thread_stack() {
+    let source = "let rec d n = if n == 0 then 0 else 1 + (d (n - 1))\nd 20000";
+    let limits = Limits { depth: Some(30000), ..Limits::default() };
+
+    assert_eq!(run_on_default_thread(source, limits), Ok("20000".to_owned()));
+}
+
+#[test]
+fn prelude_results_are_charged_against_the_memory_budget() {
+    let limits = Limits { memory: Some(1000000), ..Limits::default() };
+    let error  = "memory budget of 1000000 bytes exceeded";
+
+    assert_eq!(fails_on_default_thread("range 0, 300000000 |> len", limits), error);
+    assert_eq!(fails_on_default_thread("range 0, 10000 |> map (fun x -> x) |> map (fun x -> x) |> len", limits), error);
+    assert_eq!(fails_on_default_thread("fold (fun s _ -> concat s, s), \"ab\", (range 0, 40)", limits), error);
+    assert_eq!(fails_on_default_thread("let s = join \"\", (map to_string, (range 0, 100))\nfold (fun acc _ -> chars s), {}, (range 0, 1000)", limits), error);
+
+    assert_eq!(run_on_default_thread("range 0, 10000 |> len", limits), Ok("10000".to_owned()));
+}
+
+const LOOP: &str = "let rec loop n = loop (n + 1)\nloop 0";
+
+#[test]
+fn endless_loops_hit_the_step_limit() {
+    let interpreter = Interpreter::new();
+
+    interpreter.set_limits(Limits { steps: Some(1000), ..Limits::default() });
+
+    let errors = interpreter.run(LOOP).unwrap_err();
+
+    assert_eq!(report("loop.euc", LOOP, &errors, false), "\
+error: step limit of 1000 exceeded
+ --> loop.euc:1:18
+  |
+1 | let rec loop n = loop (n + 1)
+  |                  ^^^^^^^^^^^^
+");
+
+    // each input gets the whole budget
+    assert_eq!(interpreter.eval_str("loop").unwrap_err()[0].message, "loop: undeclared use");
+    assert_eq!(interpreter.eval_str("let rec count n = if n == 0 then 0 else count (n - 1)\ncount 100").unwrap(), Value::Int(0));
+    assert_eq!(interpreter.eval_str("count 100").unwrap(), Value::Int(0));
+}
+
+#[test]
+fn endless_loops_time_out() {
+    let interpreter = Interpreter::new();
+
+    interpreter.set_limits(Limits { timeout: Some(Duration::from_millis(100)), ..Limits::default() });
+
+    let started = Instant::now();
+
+    assert_eq!(interpreter.eval_str(LOOP).unwrap_err()[0].message, "timed out after 100ms");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+// runs the binary on `source` with `flags`, giving what it wrote to stderr
+fn cli(flags: &[&str], source: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_eucalyptus"))
+        .args(flags)
+        .args(["-e", source])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn command_line_flags_set_the_limits() {
+    assert!(cli(&["--max-steps=1000"], LOOP).starts_with("error: step limit of 1000 exceeded\n"));
+    assert!(cli(&["--timeout=100"], LOOP).starts_with("error: timed out after 100ms\n"));
+    assert!(cli(&["--max-depth=10"], "let rec d n = if n == 0 then 0 else 1 + (d (n - 1))\nd 100").starts_with("error: call depth limit of 10 exceeded\n"));
+    assert!(cli(&["--max-memory=1000"], "range 0, 1000 |> len").starts_with("error: memory budget of 1000 bytes exceeded\n"));
+}
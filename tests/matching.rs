@@ -0,0 +1,86 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+const SHAPE: &str = "type Shape = | Circle of f64 | Rect of f64 * f64 | Dot\n";
+
+#[test]
+fn arms() {
+    let name = "
+let name n =
+  match n with
+  | 0 -> \"zero\"
+  | 1 -> \"one\"
+  | _ -> \"many\"
+";
+
+    assert_eq!(eval(name, "{(name 0), (name 1), (name 7)}"), "{zero, one, many}");
+    assert_eq!(type_of(name, "name"), "Int -> Str");
+    assert_eq!(eval("", "match {1, 2} with\n| {0, _} -> 0\n| {_, y} -> y"), "2");
+
+    assert_eq!(fails("", "match 1 with\n| 1 -> 1\n| _ -> \"a\""), "(Int, Str): mismatched match arms");
+}
+
+#[test]
+fn block_arms() {
+    let source = "
+let f n =
+  let g m =
+    match m with
+    | 0 ->
+      let z = \"zero\"
+      z
+    | _ ->
+      \"other\"
+  g n
+";
+
+    assert_eq!(eval(source, "{(f 0), (f 3)}"), "{zero, other}");
+
+    let area = format!("{}{}", SHAPE, "
+let area s =
+  match s with
+  | Circle r ->
+    let squared = r * r
+    3.0 * squared
+  | Rect (w, h) -> w * h
+  | Dot -> 0.0
+");
+
+    assert_eq!(eval(&area, "map area, {Dot, (Circle 1.0), (Rect 2.0, 3.0)}"), "{0.0, 3.0, 6.0}");
+}
+
+#[test]
+fn nested() {
+    let source = format!("{}{}", SHAPE, "
+let describe s n =
+  match s with
+  | Circle _ ->
+    match n with
+    | 0 -> \"empty circle\"
+    | _ -> \"circle\"
+  | Rect (_, _) -> \"rect\"
+  | Dot -> \"dot\"
+");
+
+    assert_eq!(eval(&source, "{(describe (Circle 1.0), 0), (describe (Circle 1.0), 1), (describe Dot, 0)}"), "{empty circle, circle, dot}");
+}
+
+#[test]
+fn non_exhaustive() {
+    assert_eq!(fails(SHAPE, "match Dot with\n| Circle _ -> 1\n| Dot -> 2"), "non-exhaustive match, missing Rect");
+
+    let inner = format!("{}{}", SHAPE, "
+let f s n =
+  match n with
+  | 0 ->
+    match s with
+    | Circle _ -> 1
+  | _ -> 2
+");
+
+    assert_eq!(fails(&inner, "f"), "non-exhaustive match, missing Rect, Dot");
+    assert_eq!(fails("", "match 3 with\n| 1 -> 1\n| 2 -> 2"), "Int(3): no matching arm");
+}
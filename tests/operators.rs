@@ -0,0 +1,126 @@
+extern crate eucalyptus;
+
+mod common;
+
+use eucalyptus::*;
+
+use common::{eval, fails, type_of};
+
+#[test]
+fn comparisons() {
+    for &(op, results) in &[("<", "{true, false, false}"), (">", "{false, true, false}"), ("<=", "{true, false, true}"), (">=", "{false, true, true}")] {
+        let chars = format!("{{'a' {op} 'b', 'b' {op} 'a', 'a' {op} 'a'}}", op = op);
+        let ints  = format!("{{1 {op} 2, 2 {op} 1, 1 {op} 1}}", op = op);
+        let strs  = format!("{{\"a\" {op} \"b\", \"b\" {op} \"a\", \"a\" {op} \"a\"}}", op = op);
+
+        assert_eq!(eval("", &chars), results);
+        assert_eq!(eval("", &ints), results);
+        assert_eq!(eval("", &strs), results);
+    }
+
+    assert_eq!(type_of("", "'a' >= 'b'"), "Bool");
+    assert_eq!(fails("", "'a' < 1"), "(Char<Int): failed to compare");
+}
+
+#[test]
+fn non_strict_mode_gives_nil() {
+    let interpreter = Interpreter::new();
+    interpreter.set_strict(false);
+
+    assert_eq!(interpreter.eval_str("1 + \"a\"").unwrap(), Value::Nil);
+    assert_eq!(interpreter.eval_str("let f = 1\nf 2").unwrap(), Value::Nil);
+    assert_eq!(interpreter.eval_str("let x = 'a' < 1\nx").unwrap(), Value::Nil);
+    assert_eq!(interpreter.eval_str("let y = x and true\ny").unwrap(), Value::Nil);
+    assert_eq!(interpreter.eval_str("2 + 3").unwrap(), Value::Int(5));
+    assert_eq!(interpreter.type_of("x").unwrap(), Type::Any);
+
+    // only what gives Nil when run is let through
+    assert_eq!(interpreter.run("nope + 1").unwrap_err()[0].message, "nope: undeclared use");
+    assert_eq!(interpreter.run("if true then 1 else \"a\"").unwrap_err()[0].message, "(Int, Str): mismatched if branches");
+    assert_eq!(interpreter.run("let g (n: i64) = n\ng \"a\"").unwrap_err()[0].message, "mismatched types: expected Int, found Str");
+
+    interpreter.set_strict(true);
+    assert!(interpreter.eval_str("1 + \"a\"").is_err());
+}
+
+#[test]
+fn integer_arithmetic() {
+    let min = "let min_int = 0 - 9223372036854775807 - 1\n";
+
+    assert_eq!(eval("", "7 / 2"), "3");
+    assert_eq!(eval("", "(0 - 7) / 2"), "-3");
+    assert_eq!(eval("", "(0 - 7) % 2"), "-1");
+    assert_eq!(eval("", "2 ^ 62"), "4611686018427387904");
+    assert_eq!(eval("", "7.0 / 2.0"), "3.5");
+    assert_eq!(type_of("", "7 / 2"), "Int");
+
+    assert_eq!(fails("", "9223372036854775807 + 1"), "(9223372036854775807+1): integer overflow");
+    assert_eq!(fails(min, "min_int - 1"), "(-9223372036854775808-1): integer overflow");
+    assert_eq!(fails("", "3037000500 * 3037000500"), "(3037000500*3037000500): integer overflow");
+    assert_eq!(fails("", "2 ^ 63"), "(2^63): integer overflow");
+    assert_eq!(fails(min, "-min_int"), "(--9223372036854775808): integer overflow");
+
+    assert_eq!(fails("", "1 / 0"), "(1/0): division by zero");
+    assert_eq!(fails("", "1 % 0"), "(1%0): division by zero");
+    assert_eq!(fails(min, "min_int / (0 - 1)"), "(-9223372036854775808/-1): integer overflow");
+    assert_eq!(fails(min, "min_int % (0 - 1)"), "(-9223372036854775808%-1): integer overflow");
+}
+
+#[test]
+fn short_circuit() {
+    assert_eq!(eval("", "false and 1 / 0 == 1"), "false");
+    assert_eq!(eval("", "true or 1 / 0 == 1"), "true");
+    assert_eq!(eval("", "true and false or true"), "true");
+    assert_eq!(eval("", "let safe x = x == 0 or 10 / x > 1\n{(safe 0), (safe 5), (safe 20)}"), "{true, true, false}");
+    assert_eq!(fails("", "true and 1 / 0 == 1"), "(1/0): division by zero");
+    assert_eq!(fails("", "false or 1 / 0 == 1"), "(1/0): division by zero");
+
+    assert_eq!(eval("", "not true"), "false");
+    assert_eq!(eval("", "!(1 > 2)"), "true");
+    assert_eq!(eval("", "let x = 3\n-x * 2"), "-6");
+    assert_eq!(type_of("", "let in_range x = x >= 0 and x < 10\nin_range"), "Int -> Bool");
+    assert_eq!(fails("", "1 and true"), "(Int and Bool): failed to operate");
+}
+
+#[test]
+fn generic_operators() {
+    let source = "let add a b = a + b\nlet lt a b = a < b\nlet neg x = -x\n";
+
+    assert_eq!(type_of(source, "add"), "'a -> 'a -> 'a when 'a: number");
+    assert_eq!(type_of(source, "lt"), "'a -> 'a -> Bool when 'a: comparable");
+    assert_eq!(type_of(source, "neg"), "'a -> 'a when 'a: number");
+
+    assert_eq!(eval(source, "{(add 1, 2), (add 1.5, 2.0)}"), "{3, 3.5}");
+    assert_eq!(eval(source, "{(lt 1, 2), (lt 1.0, 2.0), (lt \"b\", \"a\"), (lt 'a', 'b')}"), "{true, true, false, true}");
+    assert_eq!(eval(source, "{(neg 2), (neg 2.5)}"), "{-2, -2.5}");
+    assert_eq!(type_of(source, "add 1.0"), "Float -> Float");
+
+    // arithmetic narrows a comparable type to the numbers
+    assert_eq!(type_of("", "let f a b = if a < b then a - b else b\nf"), "'a -> 'a -> 'a when 'a: number");
+
+    assert_eq!(fails(source, "add 1, 2.0"), "mismatched types: expected Int, found Float");
+    assert_eq!(fails(source, "lt true, false"), "mismatched types: expected a comparable type, found Bool");
+    assert_eq!(fails(source, "neg \"a\""), "mismatched types: expected a number type, found Str");
+}
+
+#[test]
+fn pipes_and_composition() {
+    let inc = "let inc x = x + 1\n";
+
+    assert_eq!(eval("", "range 0, 10 |> filter (fun x -> x % 2 == 0) |> map to_string |> join \", \""), "0, 2, 4, 6, 8");
+    assert_eq!(eval("", "{3, 1, 2} |> sort |> len"), "3");
+    assert_eq!(eval("", "to_string <| 1 + 2"), "3");
+    assert_eq!(eval(inc, "5 |> inc |> inc"), "7");
+    assert_eq!(type_of("", "range 0, 3 |> map to_string"), "Str list");
+
+    assert_eq!(eval(inc, "(inc >> to_string) 2"), "3");
+    assert_eq!(eval(inc, "(to_string << inc) 2"), "3");
+    assert_eq!(eval(inc, "let add2 = inc >> inc\n1 |> add2"), "3");
+    assert_eq!(type_of(inc, "inc >> to_string"), "Int -> Str");
+    assert_eq!(type_of(inc, "to_string << inc"), "Int -> Str");
+    assert_eq!(type_of("", "let both f g = f >> g\nboth"), "('a -> 'b) -> ('b -> 'c) -> 'a -> 'c");
+
+    assert_eq!(fails(inc, "inc >> 3"), "(Int -> Int>>Int): failed to compose");
+    assert!(fails(inc, "\"a\" |> inc").starts_with("mismatched types"));
+    assert_eq!(fails("", "1 |> 2"), "Int: can't call a non-function");
+}
@@ -0,0 +1,132 @@
+extern crate eucalyptus;
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use eucalyptus::*;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+// collects what `print` writes so it can be checked after the run
+#[derive(Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn print_and_println() {
+    let interpreter = Interpreter::new();
+    let buffer      = Buffer::default();
+
+    interpreter.set_output(buffer.clone());
+    interpreter.eval_str("print 1\nprint \" \"\nprintln {1.5, 'c'}\nprintln {\"x\"}[0]?").unwrap();
+
+    assert_eq!(String::from_utf8(buffer.0.borrow().clone()).unwrap(), "1 {1.5, c}\nSome x\n");
+    assert_eq!(type_of("", "println"), "'a -> Any");
+}
+
+#[test]
+fn to_string() {
+    assert_eq!(eval("", "to_string 12"), "12");
+    assert_eq!(eval("", "to_string 2.0"), "2.0");
+    assert_eq!(eval("", "to_string {true, {1}[5]?}"), "{true, None}");
+    assert_eq!(type_of("", "to_string"), "'a -> Str");
+}
+
+#[test]
+fn len() {
+    assert_eq!(eval("", "len {1, 2, 3}"), "3");
+    assert_eq!(eval("", "str_len \"héllo\""), "5");
+    assert_eq!(type_of("", "len"), "'a list -> Int");
+    assert_eq!(fails("", "len 1"), "mismatched types: expected 'a list, found Int");
+    assert_eq!(fails("", "len \"abc\""), "mismatched types: expected 'a list, found Str");
+    assert_eq!(fails("", "str_len {1}"), "mismatched types: expected Str, found {Int}");
+}
+
+#[test]
+fn concat() {
+    assert_eq!(eval("", "concat \"foo\", \"bar\""), "foobar");
+    assert_eq!(type_of("", "concat"), "Str -> Str -> Str");
+    assert_eq!(fails("", "concat \"foo\", 1"), "mismatched types: expected Str, found Int");
+}
+
+#[test]
+fn split_and_join() {
+    assert_eq!(eval("", "split \",\", \"a,b,,c\""), "{a, b, , c}");
+    assert_eq!(eval("", "join \"-\", {\"a\", \"b\"}"), "a-b");
+    assert_eq!(eval("", "join \", \", (split \" \", \"x y z\")"), "x, y, z");
+    assert_eq!(type_of("", "split"), "Str -> Str -> Str list");
+    assert_eq!(fails("", "split \"\", \"abc\""), "split: empty separator");
+    assert!(fails("", "join \",\", {1, 2}").starts_with("mismatched types"));
+}
+
+#[test]
+fn trim_and_chars() {
+    assert_eq!(eval("", "trim \"  hi \\n\""), "hi");
+    assert_eq!(eval("", "chars \"abc\""), "{a, b, c}");
+    assert_eq!(eval("", "(chars \"abc\")[1]"), "b");
+    assert_eq!(type_of("", "chars"), "Str -> Char list");
+}
+
+#[test]
+fn math() {
+    assert_eq!(eval("", "abs (0 - 4)"), "4");
+    assert_eq!(eval("", "abs (0.0 - 2.5)"), "2.5");
+    assert_eq!(eval("", "sqrt 9.0"), "3.0");
+    assert_eq!(eval("", "floor 2.7"), "2.0");
+    assert_eq!(eval("", "ceil 2.1"), "3.0");
+    assert_eq!(eval("", "min 3, 2"), "2");
+    assert_eq!(eval("", "max 1.5, 0.5"), "1.5");
+    assert_eq!(type_of("", "abs (0 - 4)"), "Int");
+    assert_eq!(type_of("", "abs"), "'a -> 'a when 'a: number");
+    assert_eq!(type_of("", "max"), "'a -> 'a -> 'a when 'a: number");
+    assert_eq!(type_of("", "let clamp x = max 0.0, (min 1.0, x)\nclamp"), "Float -> Float");
+    assert_eq!(type_of("", "let bigger x y = (max x, y) + 1\nbigger"), "Int -> Int -> Int");
+    assert_eq!(type_of("", "let dist a b = abs (a - b)\ndist"), "'a -> 'a -> 'a when 'a: number");
+    assert!(fails("", "sqrt 9").starts_with("mismatched types"));
+    assert!(fails("", "min 1, 2.0").starts_with("mismatched types"));
+    assert_eq!(fails("", "max \"a\", \"b\""), "mismatched types: expected a number type, found Str");
+    assert_eq!(fails("", "abs true"), "mismatched types: expected a number type, found Bool");
+}
+
+#[test]
+fn map_filter_fold() {
+    assert_eq!(eval("", "map (fun x -> x * 2), {1, 2, 3}"), "{2, 4, 6}");
+    assert_eq!(eval("", "map to_string, {1, 2}"), "{1, 2}");
+    assert_eq!(eval("", "filter (fun x -> x > 1), {1, 2, 3}"), "{2, 3}");
+    assert_eq!(eval("", "fold (fun acc x -> acc + x), 0, {1, 2, 3}"), "6");
+    assert_eq!(eval("", "let n = 10\nmap (fun x -> x + n), {1}"), "{11}");
+
+    assert_eq!(type_of("", "map"), "('a -> 'b) -> 'a list -> 'b list");
+    assert_eq!(type_of("", "map (fun x -> x > 1), {1, 2}"), "Bool list");
+    assert_eq!(type_of("", "fold"), "('a -> 'b -> 'a) -> 'a -> 'b list -> 'a");
+
+    assert!(fails("", "map (fun x -> x * 2), {\"a\"}").starts_with("mismatched types"));
+    assert!(fails("", "filter (fun x -> x), {1}").starts_with("mismatched types"));
+}
+
+#[test]
+fn range_reverse_sort() {
+    assert_eq!(eval("", "range 0, 4"), "{0, 1, 2, 3}");
+    assert_eq!(eval("", "range 3, 1"), "{}");
+    assert_eq!(eval("", "reverse (range 0, 3)"), "{2, 1, 0}");
+    assert_eq!(eval("", "sort {3, 1, 2}"), "{1, 2, 3}");
+    assert_eq!(eval("", "sort {\"b\", \"a\"}"), "{a, b}");
+    assert_eq!(eval("", "sort (chars \"cab\")"), "{a, b, c}");
+    assert_eq!(type_of("", "sort"), "'a list -> 'a list when 'a: comparable");
+    assert_eq!(type_of("", "let lowest xs = (sort xs)[0]\nlowest"), "'a list -> 'a when 'a: comparable");
+    assert!(fails("", "sort {1, 2.0}").starts_with("mismatched types"));
+    assert_eq!(fails("", "sort {{1}, {2}}"), "mismatched types: expected a comparable type, found {Int}");
+    assert_eq!(fails("", "sort {true}"), "mismatched types: expected a comparable type, found Bool");
+}
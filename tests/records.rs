@@ -0,0 +1,48 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+const ANN: &str = "let ann = { name = \"ann\"; age = 3 }\n";
+
+#[test]
+fn literals_and_fields() {
+    assert_eq!(eval(ANN, "ann"), "{ age = 3; name = ann }");
+    assert_eq!(eval(ANN, "ann.name"), "ann");
+    assert_eq!(type_of(ANN, "ann"), "{ age: Int; name: Str }");
+    assert_eq!(fails(ANN, "ann.height"), "height: no such field in { age: Int; name: Str }");
+}
+
+#[test]
+fn copy_and_update() {
+    assert_eq!(eval(ANN, "{ ann with age = 4 }"), "{ age = 4; name = ann }");
+    assert_eq!(eval(ANN, "let older = { ann with age = 4 }\nann.age"), "3");
+    assert_eq!(fails(ANN, "{ ann with height = 1 }"), "height: no such field in { age: Int; name: Str }");
+    assert_eq!(fails(ANN, "{ ann with age = \"4\" }"), "mismatched types: expected Int, found Str");
+}
+
+#[test]
+fn fields_of_parameters() {
+    let get = "let get r = r.name\n";
+
+    assert_eq!(type_of(get, "get"), "{ name: 'a; .. } -> 'a");
+    assert_eq!(eval(get, "get { name = \"x\"; age = 2 }"), "x");
+    assert_eq!(type_of(get, "get { name = 1 }"), "Int");
+
+    assert_eq!(fails(get, "let s: string = get { name = 1 }"), "mismatched types: expected Str, found Int");
+    assert_eq!(fails(get, "get { age = 1 }"), "mismatched types: expected { name: 'a; .. }, found { age: Int }");
+    assert_eq!(fails(get, "get 1"), "mismatched types: expected { name: 'a; .. }, found Int");
+}
+
+#[test]
+fn updates_of_parameters() {
+    let older = "let older p = { p with age = p.age + 1 }\n";
+
+    assert_eq!(type_of(older, "older"), "{ age: Int; .. } -> { age: Int; .. }");
+    assert_eq!(eval(&format!("{}{}", ANN, older), "older ann"), "{ age = 4; name = ann }");
+    assert_eq!(type_of(&format!("{}{}", ANN, older), "older ann"), "{ age: Int; name: Str }");
+
+    assert_eq!(fails(older, "older { name = \"a\" }"), "mismatched types: expected { age: Int; .. }, found { name: Str }");
+    assert_eq!(fails(older, "let n: string = (older { age = 1 }).age"), "mismatched types: expected Str, found Int");
+}
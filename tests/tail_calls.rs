@@ -0,0 +1,45 @@
+use std::process::Command;
+
+fn eval(source: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_eucalyptus"))
+        .args(["-e", source])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn countdown_runs_in_constant_stack() {
+    let source = "let countdown n = if n == 0 then \"done\" else countdown (n - 1)\ncountdown 1000000\n";
+
+    assert!(eval(source).contains("\"done\""));
+}
+
+#[test]
+fn match_arm_is_a_tail_position() {
+    let source = "
+let rec sum i acc =
+  match i with
+  | 0 -> acc
+  | _ ->
+    let j = i - 1
+    sum j, acc + i
+sum 100000, 0
+";
+
+    assert!(eval(source).contains("5000050000"));
+}
+
+#[test]
+fn mutual_recursion_in_tail_position() {
+    let source = "
+let rec even n = if n == 0 then true else odd (n - 1)
+and odd n = if n == 0 then false else even (n - 1)
+even 100001
+";
+
+    assert!(eval(source).contains("false"));
+}
@@ -0,0 +1,70 @@
+extern crate eucalyptus;
+
+mod common;
+
+use common::{eval, fails, type_of};
+
+const SHAPE: &str = "
+type Shape =
+  | Circle of f64
+  | Rect of f64 * f64
+  | Dot
+";
+
+#[test]
+fn constructors() {
+    assert_eq!(eval(SHAPE, "Circle 2.0"), "Circle 2.0");
+    assert_eq!(eval(SHAPE, "Rect 1.0, 2.0"), "Rect (1.0, 2.0)");
+    assert_eq!(eval(SHAPE, "Dot"), "Dot");
+    assert_eq!(eval(SHAPE, "map Circle, {1.0}"), "{Circle 1.0}");
+    assert_eq!(eval(SHAPE, "let square = Rect 1.0\nsquare 3.0"), "Rect (1.0, 3.0)");
+
+    assert_eq!(type_of(SHAPE, "Rect"), "Float -> Float -> Shape");
+    assert_eq!(type_of(SHAPE, "Dot"), "Shape");
+    assert_eq!(fails(SHAPE, "Circle 1"), "mismatched types: expected Float, found Int");
+}
+
+#[test]
+fn destructuring() {
+    let area = format!("{}{}", SHAPE, "
+let area s =
+  match s with
+  | Circle r -> 3.0 * r * r
+  | Rect (w, h) -> w * h
+  | Dot -> 0.0
+");
+
+    assert_eq!(eval(&area, "area (Rect 2.0, 3.0)"), "6.0");
+    assert_eq!(eval(&area, "map area, {Dot, Circle 1.0}"), "{0.0, 3.0}");
+    assert_eq!(type_of(&area, "area"), "Shape -> Float");
+}
+
+#[test]
+fn recursive_unions() {
+    let source = "
+type Tree = | Leaf | Node of Tree * i64 * Tree
+let rec sum t =
+  match t with
+  | Leaf -> 0
+  | Node (l, v, r) -> (sum l) + v + (sum r)
+sum (Node (Node Leaf, 1, Leaf), 2, Leaf)
+";
+
+    assert_eq!(eval(SHAPE, source), "3");
+}
+
+#[test]
+fn exhaustiveness() {
+    assert_eq!(fails(SHAPE, "match Dot with\n| Circle _ -> 1\n| Dot -> 2"), "non-exhaustive match, missing Rect");
+    assert_eq!(fails(SHAPE, "match Dot with\n| Circle 1.0 -> 1\n| Rect (_, _) -> 2\n| Dot -> 3"), "non-exhaustive match, missing Circle");
+    assert_eq!(eval(SHAPE, "match Dot with\n| Circle _ -> 1\n| _ -> 2"), "2");
+}
+
+#[test]
+fn errors() {
+    assert_eq!(fails(SHAPE, "type T = | A of Missing"), "Missing: undeclared type");
+    assert_eq!(fails(SHAPE, "match 1 with\n| Square x -> x"), "Square: undeclared constructor");
+    assert_eq!(fails(SHAPE, "match Dot with\n| Rect x -> 1\n| _ -> 2"), "Rect: expected 2 field(s), found 1");
+    assert!(fails(SHAPE, "match 1 with\n| Dot -> 1").contains("pattern can't match Int"));
+    assert_eq!(fails(SHAPE, "type T = | A | A"), "A: duplicate constructor");
+}